@@ -1,5 +1,44 @@
-use super::IS_EXAMPLE;
+use super::{CAPTURE, IS_EXAMPLE, PARAMS};
+use std::fmt::Arguments;
 
 pub fn is_example() -> bool {
     *IS_EXAMPLE.read().unwrap()
 }
+
+/// Returns the value of a context parameter, passed as `<name>=<value>` when running a day.
+pub fn param(name: &str) -> Option<String> {
+    PARAMS.read().unwrap().get(name).cloned()
+}
+
+/// Whether a boolean context parameter is set, as `<name>=1` or any value other than `0`/`false`.
+pub fn flag(name: &str) -> bool {
+    param(name).is_some_and(|value| value != "0" && value != "false")
+}
+
+/// Replaces all context parameters.
+pub fn set_params<I: IntoIterator<Item = (String, String)>>(params: I) {
+    *PARAMS.write().unwrap() = params.into_iter().collect();
+}
+
+/// Prints a line of solution output, or saves it if output is being captured (see [capture]).
+pub fn output(args: Arguments) {
+    CAPTURE.with_borrow_mut(|capture| match capture {
+        Some(lines) => lines.extend(args.to_string().split('\n').map(str::to_owned)),
+        None => println!("{args}"),
+    })
+}
+
+/// Runs a function, returning its result and the output lines it produced on this thread.
+pub fn capture<T, F: FnOnce() -> T>(func: F) -> (T, Vec<String>) {
+    CAPTURE.set(Some(Vec::new()));
+    // Restore regular output, even if the function panics
+    struct Guard;
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            CAPTURE.set(None);
+        }
+    }
+    let _guard = Guard;
+    let result = func();
+    (result, CAPTURE.take().unwrap_or_default())
+}
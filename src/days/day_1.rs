@@ -1,7 +1,9 @@
-use super::DayInfo;
-use std::collections::{BinaryHeap, HashMap};
-use std::iter::Map;
-use std::str::Lines;
+use super::{number, DayInfo, ParseError};
+use crate::api::flag;
+use kust::ScopeFunctions;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{stdin, BufRead};
 
 const EXAMPLE: &str = "\
 3   4
@@ -11,7 +13,7 @@ const EXAMPLE: &str = "\
 3   9
 3   3";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Historian Hysteria",
     part1,
     part2,
@@ -19,40 +21,431 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-fn parse(input: &str) -> Map<Lines, fn(&str) -> (u32, u32)> {
-    input.lines().map(|line| {
-        let mut nums = line.split_whitespace();
-        (
-            nums.next().unwrap().parse::<u32>().unwrap(),
-            nums.next().unwrap().parse::<u32>().unwrap(),
+/// Lists of location IDs (one per column), in input order. All lists have the same length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lists {
+    columns: Vec<Vec<u32>>,
+}
+
+impl Lists {
+    /// Creates the lists, which must be at least two, all of the same length.
+    pub fn new(columns: Vec<Vec<u32>>) -> Result<Self, ParseError> {
+        if columns.len() < 2 {
+            return Err(ParseError::new("expected at least two lists"));
+        }
+        let len = columns[0].len();
+        if let Some(index) = columns.iter().position(|column| column.len() != len) {
+            return Err(ParseError::new(format!(
+                "list {} has {} values, but list 1 has {len}",
+                index + 1,
+                columns[index].len()
+            )));
+        }
+        Ok(Self { columns })
+    }
+
+    pub fn columns(&self) -> &[Vec<u32>] {
+        &self.columns
+    }
+}
+
+/// Parses whitespace-separated columns of numbers. Every line must have the same number of columns,
+/// a shorter or longer line means the lists have different lengths.
+pub fn parse(input: &str) -> Result<Lists, ParseError> {
+    let mut columns: Vec<Vec<u32>> = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let nums = line
+            .split_whitespace()
+            .map(number)
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|e| e.at_line(index))?;
+        if index == 0 {
+            if nums.len() < 2 {
+                return Err(ParseError::new("expected at least two lists").at_line(index));
+            }
+            columns = vec![Vec::new(); nums.len()];
+        } else if nums.len() != columns.len() {
+            return Err(ParseError::new(format!(
+                "expected {} values, found {} (lists have different lengths)",
+                columns.len(),
+                nums.len()
+            ))
+            .at_line(index));
+        }
+        for (column, num) in columns.iter_mut().zip(nums) {
+            column.push(num);
+        }
+    }
+    if columns.is_empty() {
+        return Err(ParseError::new("expected at least two lists"));
+    }
+    Lists::new(columns)
+}
+
+/// Numbers at the same position in each sorted list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pairing {
+    pub values: Vec<u32>,
+    /// Difference between the largest and smallest value
+    pub distance: u32,
+}
+
+/// Similarity score added by a number of the first list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contribution {
+    pub value: u32,
+    /// Occurrences of the number in all the other lists
+    pub occurrences: u32,
+    pub score: u64,
+}
+
+/// Full comparison of the lists, giving both totals and how they were obtained.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reconciliation {
+    pub pairings: Vec<Pairing>,
+    /// For each list, the distinct numbers which appear in no other list (sorted)
+    pub unmatched: Vec<Vec<u32>>,
+    /// Contributions of the numbers of the first list, in sorted order
+    pub contributions: Vec<Contribution>,
+    pub total_distance: u64,
+    pub similarity_score: u64,
+}
+
+pub fn reconcile(lists: &Lists) -> Reconciliation {
+    let sorted: Vec<Vec<u32>> = lists
+        .columns
+        .iter()
+        .map(|column| column.clone().apply(|it| it.sort_unstable()))
+        .collect();
+
+    let pairings: Vec<Pairing> = (0..sorted[0].len())
+        .map(|i| {
+            let values: Vec<u32> = sorted.iter().map(|column| column[i]).collect();
+            let min = values.iter().min().unwrap();
+            let max = values.iter().max().unwrap();
+            Pairing {
+                distance: max - min,
+                values,
+            }
+        })
+        .collect();
+
+    let counts: Vec<HashMap<u32, u32>> = lists
+        .columns
+        .iter()
+        .map(|column| {
+            let mut count = HashMap::new();
+            for &num in column {
+                count.entry(num).and_modify(|e| *e += 1).or_insert(1);
+            }
+            count
+        })
+        .collect();
+
+    let unmatched = sorted
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let mut values: Vec<u32> = column
+                .iter()
+                .copied()
+                .filter(|num| {
+                    (counts.iter().enumerate())
+                        .all(|(i, count)| i == index || !count.contains_key(num))
+                })
+                .collect();
+            values.dedup();
+            values
+        })
+        .collect();
+
+    let contributions: Vec<Contribution> = sorted[0]
+        .iter()
+        .map(|&value| {
+            let occurrences = counts[1..]
+                .iter()
+                .map(|count| count.get(&value).copied().unwrap_or(0))
+                .sum();
+            Contribution {
+                value,
+                occurrences,
+                score: value as u64 * occurrences as u64,
+            }
+        })
+        .collect();
+
+    Reconciliation {
+        total_distance: pairings.iter().map(|pairing| pairing.distance as u64).sum(),
+        similarity_score: contributions.iter().map(|c| c.score).sum(),
+        pairings,
+        unmatched,
+        contributions,
+    }
+}
+
+impl Display for Reconciliation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "Pairs (sorted) and distance:")?;
+        for pairing in &self.pairings {
+            for value in &pairing.values {
+                write!(f, "{value:>8}")?;
+            }
+            writeln!(f, "  -> {}", pairing.distance)?;
+        }
+
+        for (index, values) in self.unmatched.iter().enumerate() {
+            let values: Vec<String> = values.iter().map(u32::to_string).collect();
+            writeln!(f, "Only in list {}: {}", index + 1, values.join(", "))?;
+        }
+
+        writeln!(f, "Similarity contributions:")?;
+        for c in &self.contributions {
+            writeln!(f, "{:>8} x {} = {}", c.value, c.occurrences, c.score)?;
+        }
+
+        writeln!(f, "Total distance: {}", self.total_distance)?;
+        write!(f, "Similarity score: {}", self.similarity_score)
+    }
+}
+
+/// Sum of distances between the lists, pairing up numbers in sorted order.
+pub fn total_distance(lists: &Lists) -> u64 {
+    reconcile(lists).total_distance
+}
+
+/// Sum of numbers in the first list, each multiplied by its number of occurrences in the others.
+pub fn similarity_score(lists: &Lists) -> u64 {
+    reconcile(lists).similarity_score
+}
+
+/// Multiset of numbers supporting ranked access, as a binary trie over the bits of the numbers.
+/// Each node counts the numbers below it.
+#[derive(Debug, Clone)]
+struct OrderStatistics {
+    /// Children (0 = none) and count of every node, the root being node 0
+    nodes: Vec<([u32; 2], u32)>,
+}
+
+impl OrderStatistics {
+    fn new() -> Self {
+        Self {
+            nodes: vec![([0; 2], 0)],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes[0].1 as usize
+    }
+
+    fn count(&self, node: u32) -> usize {
+        // Node 0 can never be a child, so it also stands for a missing (empty) one
+        if node == 0 {
+            0
+        } else {
+            self.nodes[node as usize].1 as usize
+        }
+    }
+
+    fn insert(&mut self, value: u32) {
+        let mut node = 0;
+        self.nodes[0].1 += 1;
+        for bit in (0..u32::BITS).rev() {
+            let side = (value >> bit & 1) as usize;
+            if self.nodes[node].0[side] == 0 {
+                self.nodes[node].0[side] = self.nodes.len() as u32;
+                self.nodes.push(([0; 2], 0));
+            }
+            node = self.nodes[node].0[side] as usize;
+            self.nodes[node].1 += 1;
+        }
+    }
+
+    /// Number of values smaller than the given one, which is also the rank it would be inserted at.
+    fn rank(&self, value: u32) -> usize {
+        let mut rank = 0;
+        let mut node = 0;
+        for bit in (0..u32::BITS).rev() {
+            let [left, right] = self.nodes[node].0;
+            if value >> bit & 1 == 1 {
+                rank += self.count(left);
+                node = right as usize;
+            } else {
+                node = left as usize;
+            }
+            if node == 0 {
+                break;
+            }
+        }
+        rank
+    }
+
+    /// Number at the given rank in sorted order.
+    fn select(&self, mut rank: usize) -> u32 {
+        let mut value = 0;
+        let mut node = 0;
+        for _ in 0..u32::BITS {
+            let [left, right] = self.nodes[node].0;
+            let left_count = self.count(left);
+            value <<= 1;
+            if rank < left_count {
+                node = left as usize;
+            } else {
+                rank -= left_count;
+                value |= 1;
+                node = right as usize;
+            }
+        }
+        value
+    }
+}
+
+/// Total distance and similarity score of two lists, kept up to date as pairs of numbers are added.
+///
+/// The lists are never sorted: adding a pair only changes the sorted pairing between the ranks at
+/// which its two numbers are inserted, so only these pairs are recomputed. This is still linear in
+/// the number of pairs in the worst case, when the two numbers land at opposite ends of the lists,
+/// but cheap when they are close in rank, as for lists drawn from similar distributions.
+#[derive(Debug, Clone)]
+pub struct RunningTotals {
+    first: OrderStatistics,
+    second: OrderStatistics,
+    first_count: HashMap<u32, u32>,
+    second_count: HashMap<u32, u32>,
+    total_distance: u64,
+    similarity_score: u64,
+}
+
+impl RunningTotals {
+    pub fn new() -> Self {
+        Self {
+            first: OrderStatistics::new(),
+            second: OrderStatistics::new(),
+            first_count: HashMap::new(),
+            second_count: HashMap::new(),
+            total_distance: 0,
+            similarity_score: 0,
+        }
+    }
+
+    /// Number of pairs added so far.
+    pub fn len(&self) -> usize {
+        self.first.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn total_distance(&self) -> u64 {
+        self.total_distance
+    }
+
+    pub fn similarity_score(&self) -> u64 {
+        self.similarity_score
+    }
+
+    /// Sum of distances of the sorted pairs with ranks in the given range.
+    fn distance(&self, ranks: std::ops::Range<usize>) -> u64 {
+        ranks
+            .map(|i| self.first.select(i).abs_diff(self.second.select(i)) as u64)
+            .sum()
+    }
+
+    /// Adds a number to each list, in time proportional to the number of pairs between the two
+    /// insertion ranks (up to all of them), each looked up with a walk down the tries.
+    pub fn push(&mut self, a: u32, b: u32) {
+        // Pairs before the lowest and after the highest insertion rank keep the same numbers
+        let (rank_a, rank_b) = (self.first.rank(a), self.second.rank(b));
+        let (low, high) = (rank_a.min(rank_b), rank_a.max(rank_b));
+        self.total_distance -= self.distance(low..high);
+        self.first.insert(a);
+        self.second.insert(b);
+        self.total_distance += self.distance(low..high + 1);
+
+        *self.first_count.entry(a).or_insert(0) += 1;
+        *self.second_count.entry(b).or_insert(0) += 1;
+        let a_count = self.second_count.get(&a).copied().unwrap_or(0);
+        // Occurrences of b in the first list, before a was added
+        let b_count = self.first_count.get(&b).copied().unwrap_or(0) - (a == b) as u32;
+        self.similarity_score += a as u64 * a_count as u64 + b as u64 * b_count as u64;
+    }
+}
+
+impl Default for RunningTotals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extend<(u32, u32)> for RunningTotals {
+    fn extend<T: IntoIterator<Item = (u32, u32)>>(&mut self, iter: T) {
+        for (a, b) in iter {
+            self.push(a, b);
+        }
+    }
+}
+
+/// Reads pairs from stdin, in batches separated by blank lines, printing the totals after each one.
+fn stream() {
+    let mut totals = RunningTotals::new();
+    let print = |totals: &RunningTotals| {
+        output!(
+            "{} pairs: total distance {}, similarity score {}",
+            totals.len(),
+            totals.total_distance,
+            totals.similarity_score
         )
-    })
+    };
+    let mut batch = 0;
+    for (index, line) in stdin().lock().lines().enumerate() {
+        let line = line.unwrap();
+        if line.trim().is_empty() {
+            if batch > 0 {
+                print(&totals);
+            }
+            batch = 0;
+            continue;
+        }
+        let mut nums = line.split_whitespace().map(number::<u32>);
+        match (nums.next(), nums.next(), nums.next()) {
+            (Some(Ok(a)), Some(Ok(b)), None) => {
+                totals.push(a, b);
+                batch += 1;
+            }
+            _ => output!(
+                "{}",
+                ParseError::new("expected two numbers, line skipped").at_line(index)
+            ),
+        }
+    }
+    if batch > 0 {
+        print(&totals);
+    }
+}
+
+/// Prints the reconciliation report if the `report` parameter is set.
+fn print_report(reconciliation: &Reconciliation) {
+    if flag("report") {
+        output!("{reconciliation}\n");
+    }
 }
 
 fn part1(input: &str) {
-    let mut first: BinaryHeap<u32> = BinaryHeap::new();
-    let mut second: BinaryHeap<u32> = BinaryHeap::new();
-    parse(input).for_each(|(a, b)| {
-        first.push(a);
-        second.push(b);
-    });
-    let mut sum: u32 = 0;
-    while !first.is_empty() {
-        sum += first.pop().unwrap().abs_diff(second.pop().unwrap());
+    if flag("stream") {
+        stream();
+        return;
     }
-    println!("{sum}")
+    let reconciliation = reconcile(&parse(input).unwrap());
+    print_report(&reconciliation);
+    output!("{}", reconciliation.total_distance);
 }
 
 fn part2(input: &str) {
-    let mut count: HashMap<u32, u32> = HashMap::new();
-    let mut first: Vec<u32> = Vec::new();
-    parse(input).for_each(|(a, b)| {
-        first.push(a);
-        count.entry(b).and_modify(|e| *e += 1).or_insert(1);
-    });
-    let mut similarity: u64 = 0;
-    for num in first {
-        similarity += num as u64 * count.get(&num).map(|&n| n).unwrap_or(0) as u64;
-    }
-    println!("{similarity}")
+    if flag("stream") {
+        stream();
+        return;
+    }
+    let reconciliation = reconcile(&parse(input).unwrap());
+    print_report(&reconciliation);
+    output!("{}", reconciliation.similarity_score);
 }
@@ -1,5 +1,5 @@
-use super::DayInfo;
-use std::collections::HashSet;
+use super::{DayInfo, ParseError};
+use crate::api::param;
 
 const EXAMPLE: &str = "\
 89010123
@@ -11,7 +11,7 @@ const EXAMPLE: &str = "\
 01329801
 10456732";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Hoof It",
     part1,
     part2,
@@ -21,99 +21,152 @@ pub const INFO: DayInfo = DayInfo {
 
 const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
 
-fn run<F: FnMut(&Vec<Vec<u32>>, usize, usize) -> u32>(input: &str, mut dfs: F) -> u32 {
-    let map: Vec<Vec<u32>> = input
-        .lines()
-        .map(|line| line.chars().map(|c| c.to_digit(10).unwrap()).collect())
-        .collect();
-    let mut score = 0;
+/// Height of every position on the map, None where it cannot be walked on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopoMap {
+    pub heights: Vec<Vec<Option<u32>>>,
+}
 
-    for i in 0..map.len() {
-        for j in 0..map[i].len() {
-            score += dfs(&map, i, j);
-        }
+/// Parses the map. Cells that aren't digits (such as `.`) are impassable.
+pub fn parse(input: &str) -> Result<TopoMap, ParseError> {
+    let heights: Vec<Vec<Option<u32>>> = (input.lines())
+        .map(|line| line.chars().map(|c| c.to_digit(10)).collect())
+        .collect();
+    if let Some(index) = heights.iter().position(|row| row.len() != heights[0].len()) {
+        return Err(ParseError::new("rows have different lengths").at_line(index));
     }
-    score
+    Ok(TopoMap { heights })
 }
 
-fn part1(input: &str) {
-    fn dfs(
-        map: &Vec<Vec<u32>>,
-        visited: &mut Vec<Vec<bool>>,
-        y: usize,
-        x: usize,
-        expected: u32,
-    ) -> u32 {
-        if visited[y][x] || map[y][x] != expected {
-            return 0;
-        }
-        visited[y][x] = true;
+/// Heights trails go between, one step at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trails {
+    pub start: u32,
+    pub end: u32,
+}
 
-        if expected == 9 {
-            return 1;
-        }
-        let mut score = 0;
-        for (dx, dy) in DIRECTIONS {
-            let x2 = x as i32 + dx;
-            let y2 = y as i32 + dy;
-            if x2 < 0 || y2 < 0 || x2 >= map[0].len() as i32 || y2 >= map.len() as i32 {
-                continue;
-            }
-            score += dfs(map, visited, y2 as usize, x2 as usize, expected + 1);
+impl Trails {
+    /// Heights set by the `start_height` and `end_height` context parameters, defaulting to the
+    /// puzzle's 0 to 9. Trails go down if the end is below the start.
+    pub fn from_params() -> Self {
+        let height = |name, default| param(name).map_or(default, |h| h.parse().unwrap());
+        Self {
+            start: height("start_height", 0),
+            end: height("end_height", 9),
         }
-        score
     }
 
-    println!(
-        "{}",
-        run(input, |map, y, x| {
-            let mut vis = vec![vec![false; map[0].len()]; map.len()];
-            dfs(map, &mut vis, y, x, 0)
-        })
-    );
+    /// Heights along a trail, in order.
+    fn heights(&self) -> Vec<u32> {
+        if self.start <= self.end {
+            (self.start..=self.end).collect()
+        } else {
+            (self.end..=self.start).rev().collect()
+        }
+    }
 }
 
-fn part2(input: &str) {
-    fn dfs(
-        map: &Vec<Vec<u32>>,
-        paths: &mut HashSet<Vec<(usize, usize)>>,
-        mut path: Vec<(usize, usize)>,
-        y: usize,
-        x: usize,
-        expected: u32,
-    ) {
-        if map[y][x] != expected {
-            return;
-        }
-        path.push((x, y));
+impl Default for Trails {
+    fn default() -> Self {
+        Self { start: 0, end: 9 }
+    }
+}
 
-        if expected == 9 {
-            paths.insert(path);
-            return;
-        }
-        for (dx, dy) in DIRECTIONS {
-            let x2 = x as i32 + dx;
-            let y2 = y as i32 + dy;
-            if x2 < 0 || y2 < 0 || x2 >= map[0].len() as i32 || y2 >= map.len() as i32 {
-                continue;
+/// Computes a value for each cell on trails, from the end height back to the start: cells at the
+/// end height get `end(index)` (numbered in reading order), other cells `add` the values of the
+/// neighbouring cells one step further along. Returns the values of the trailheads.
+///
+/// Values are only kept for one height at a time, the previous one being dropped as soon as the
+/// next is computed.
+fn propagate<T: Clone>(
+    map: &TopoMap,
+    trails: Trails,
+    zero: T,
+    end: impl Fn(usize) -> T,
+    add: impl Fn(&mut T, &T),
+) -> Vec<T> {
+    let heights = &map.heights;
+    let (height, width) = (heights.len(), heights.first().map_or(0, Vec::len));
+    let mut levels: Vec<Vec<(usize, usize)>> = vec![Vec::new(); 10];
+    // Index of each cell among the cells of the same height
+    let mut slots = vec![vec![0; width]; height];
+    for (y, row) in heights.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            if let Some(h) = cell {
+                slots[y][x] = levels[h as usize].len();
+                levels[h as usize].push((x, y));
             }
-            dfs(
-                map,
-                paths,
-                path.clone(),
-                y2 as usize,
-                x2 as usize,
-                expected + 1,
-            );
         }
     }
+    let cells = |level: u32| levels.get(level as usize).map_or(&[][..], Vec::as_slice);
+    let trail = trails.heights();
+    let mut values: Vec<T> = (0..cells(trails.end).len()).map(end).collect();
+    for pair in trail.windows(2).rev() {
+        let (level, next) = (pair[0], pair[1]);
+        values = (cells(level).iter())
+            .map(|&(x, y)| {
+                let mut value = zero.clone();
+                for (dx, dy) in DIRECTIONS {
+                    let (x2, y2) = (x as i32 + dx, y as i32 + dy);
+                    if x2 < 0 || y2 < 0 || x2 >= width as i32 || y2 >= height as i32 {
+                        continue;
+                    }
+                    let (x2, y2) = (x2 as usize, y2 as usize);
+                    if heights[y2][x2] == Some(next) {
+                        add(&mut value, &values[slots[y2][x2]]);
+                    }
+                }
+                value
+            })
+            .collect();
+    }
+    values
+}
 
-    println!(
-        "{}",
-        run(input, |map, y, x| {
-            let mut paths: HashSet<Vec<(usize, usize)>> = HashSet::new();
-            dfs(map, &mut paths, Vec::new(), y, x, 0);
-            paths.len() as u32
-        })
+/// Number of end cells reachable from each trailhead, propagating bitsets of reachable ends.
+pub fn scores(map: &TopoMap, trails: Trails) -> Vec<u32> {
+    let ends = (map.heights.iter().flatten())
+        .filter(|&&h| h == Some(trails.end))
+        .count();
+    let words = ends.div_ceil(64);
+    let bitsets = propagate(
+        map,
+        trails,
+        vec![0u64; words],
+        |index| {
+            let mut bitset = vec![0; words];
+            bitset[index / 64] |= 1 << (index % 64);
+            bitset
+        },
+        |value, next| value.iter_mut().zip(next).for_each(|(a, b)| *a |= b),
     );
+    (bitsets.iter())
+        .map(|bitset| bitset.iter().map(|word| word.count_ones()).sum())
+        .collect()
+}
+
+/// Number of distinct trails from each trailhead, summing the trails from each cell in height
+/// order.
+pub fn ratings(map: &TopoMap, trails: Trails) -> Vec<u64> {
+    propagate(map, trails, 0, |_| 1, |value, next| *value += next)
+}
+
+/// Sum of trailhead scores (number of peaks reachable from each trailhead).
+pub fn trailhead_scores(map: &TopoMap) -> u32 {
+    scores(map, Trails::default()).iter().sum()
+}
+
+/// Sum of trailhead ratings (number of distinct hiking trails from each trailhead).
+pub fn trailhead_ratings(map: &TopoMap) -> u64 {
+    ratings(map, Trails::default()).iter().sum()
+}
+
+fn part1(input: &str) {
+    let scores = scores(&parse(input).unwrap(), Trails::from_params());
+    output!("{}", scores.iter().sum::<u32>());
+}
+
+fn part2(input: &str) {
+    let ratings = ratings(&parse(input).unwrap(), Trails::from_params());
+    output!("{}", ratings.iter().sum::<u64>());
 }
@@ -1,8 +1,11 @@
-use super::DayInfo;
-use std::array;
+use super::{number, DayInfo, ParseError};
+use crate::api::{flag, param};
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Plutonian Pebbles",
     part1,
     part2,
@@ -10,61 +13,314 @@ pub const INFO: DayInfo = DayInfo {
     example2: "125 17",
 };
 
-fn part1(input: &str) {
-    const MAX_STEP: u32 = 25;
-
-    fn calc(num: u64, step: u32) -> u32 {
-        if step == MAX_STEP {
-            1
-        } else if num == 0 {
-            calc(1, step + 1)
-        } else {
-            let digits = num.ilog10() + 1;
-            if digits % 2 == 0 {
-                let t = 10u64.pow(digits / 2);
-                calc(num / t, step + 1) + calc(num % t, step + 1)
-            } else {
-                calc(num * 2024, step + 1)
+/// Numbers engraved on stones and used to count them.
+pub trait Number: Clone + Eq + Hash + Display {
+    fn from_u64(n: u64) -> Self;
+    fn is_zero(&self) -> bool;
+    /// Number of decimal digits, 1 for zero
+    fn digits(&self) -> u32;
+    /// Leading digits and the last `count` digits, as numbers
+    fn split_digits(&self, count: u32) -> (Self, Self);
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+}
+
+impl Number for u64 {
+    fn from_u64(n: u64) -> Self {
+        n
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn digits(&self) -> u32 {
+        self.checked_ilog10().unwrap_or(0) + 1
+    }
+
+    fn split_digits(&self, count: u32) -> (Self, Self) {
+        let shift = 10u64.pow(count);
+        (self / shift, self % shift)
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u64::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        u64::checked_mul(*self, *other)
+    }
+}
+
+/// Unbounded unsigned integer, for counts (or numbers) that don't fit in 64 bits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    /// Decimal digits, least significant first, without leading zeros
+    digits: Vec<u8>,
+}
+
+impl BigUint {
+    fn from_digits(mut digits: Vec<u8>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self { digits }
+    }
+}
+
+impl Number for BigUint {
+    fn from_u64(mut n: u64) -> Self {
+        let mut digits = Vec::new();
+        while n > 0 {
+            digits.push((n % 10) as u8);
+            n /= 10;
+        }
+        Self { digits }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    fn digits(&self) -> u32 {
+        self.digits.len().max(1) as u32
+    }
+
+    fn split_digits(&self, count: u32) -> (Self, Self) {
+        let count = (count as usize).min(self.digits.len());
+        let (low, high) = self.digits.split_at(count);
+        (
+            Self::from_digits(high.to_vec()),
+            Self::from_digits(low.to_vec()),
+        )
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        let mut digits = Vec::with_capacity(self.digits.len().max(other.digits.len()) + 1);
+        let mut carry = 0;
+        for i in 0..self.digits.len().max(other.digits.len()) {
+            let sum = carry
+                + self.digits.get(i).copied().unwrap_or(0)
+                + other.digits.get(i).copied().unwrap_or(0);
+            digits.push(sum % 10);
+            carry = sum / 10;
+        }
+        digits.push(carry);
+        Some(Self::from_digits(digits))
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let mut sums = vec![0u32; self.digits.len() + other.digits.len() + 1];
+        for (i, &a) in self.digits.iter().enumerate() {
+            for (j, &b) in other.digits.iter().enumerate() {
+                sums[i + j] += a as u32 * b as u32;
+            }
+            // Keep sums small enough not to overflow
+            for k in i..sums.len() - 1 {
+                sums[k + 1] += sums[k] / 10;
+                sums[k] %= 10;
             }
         }
+        Some(Self::from_digits(sums.iter().map(|&d| d as u8).collect()))
     }
+}
 
-    println!(
-        "{}",
-        input
-            .split_whitespace()
-            .map(|s| calc(s.parse().unwrap(), 0))
-            .sum::<u32>()
-    );
+impl Display for BigUint {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if self.digits.is_empty() {
+            return f.write_str("0");
+        }
+        let digits: String = (self.digits.iter().rev())
+            .map(|&d| char::from(b'0' + d))
+            .collect();
+        f.write_str(&digits)
+    }
 }
 
-fn part2(input: &str) {
-    const MAX_STEP: usize = 75;
+/// What a rule does to a stone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome<N> {
+    /// The rule doesn't apply, the next one is tried
+    Skip,
+    /// The stone is replaced by stones with these numbers
+    Replace(Vec<N>),
+    /// The new numbers don't fit
+    Overflow,
+}
 
-    let mut steps: [HashMap<u64, u128>; MAX_STEP + 1] = array::from_fn(|_| HashMap::new());
+/// Rule changing stones at each blink.
+#[derive(Debug, Clone)]
+pub struct Rule<N> {
+    pub name: &'static str,
+    pub apply: fn(&N) -> Outcome<N>,
+}
 
-    for num in input.split_whitespace().map(|s| s.parse::<u64>().unwrap()) {
-        *steps[0].entry(num).or_default() += 1;
+/// Stones engraved with 0 are replaced by a stone engraved with 1.
+pub fn zero_to_one<N: Number>(n: &N) -> Outcome<N> {
+    if n.is_zero() {
+        Outcome::Replace(vec![N::from_u64(1)])
+    } else {
+        Outcome::Skip
     }
+}
 
-    for step in 1..=MAX_STEP {
-        let (s1, s2) = steps.split_at_mut(step);
-        let current = s2.first_mut().unwrap();
-        for (&num, &count) in s1.last().unwrap() {
-            if num == 0 {
-                *current.entry(1).or_default() += count;
-                continue;
+/// Stones with an even number of digits are split into the left and right halves of the digits.
+pub fn split_even_digits<N: Number>(n: &N) -> Outcome<N> {
+    let digits = n.digits();
+    if digits.is_multiple_of(2) {
+        let (left, right) = n.split_digits(digits / 2);
+        Outcome::Replace(vec![left, right])
+    } else {
+        Outcome::Skip
+    }
+}
+
+/// Stones have their number multiplied by 2024.
+pub fn multiply_by_2024<N: Number>(n: &N) -> Outcome<N> {
+    match n.checked_mul(&N::from_u64(2024)) {
+        Some(n) => Outcome::Replace(vec![n]),
+        None => Outcome::Overflow,
+    }
+}
+
+/// Rules applied to each stone at each blink: the first one applying to a stone replaces it.
+/// Stones no rule applies to stay the same.
+#[derive(Debug, Clone)]
+pub struct Rules<N> {
+    rules: Vec<Rule<N>>,
+}
+
+impl<N: Number> Rules<N> {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Adds a rule, tried after the previous ones.
+    pub fn with(mut self, name: &'static str, apply: fn(&N) -> Outcome<N>) -> Self {
+        self.rules.push(Rule { name, apply });
+        self
+    }
+
+    /// The puzzle's rules.
+    pub fn puzzle() -> Self {
+        Self::new()
+            .with("zero", zero_to_one)
+            .with("split", split_even_digits)
+            .with("multiply", multiply_by_2024)
+    }
+
+    pub fn rules(&self) -> &[Rule<N>] {
+        &self.rules
+    }
+
+    /// Stones a stone turns into, or None if a number overflows.
+    fn apply(&self, n: &N) -> Option<Vec<N>> {
+        for rule in &self.rules {
+            match (rule.apply)(n) {
+                Outcome::Skip => (),
+                Outcome::Replace(stones) => return Some(stones),
+                Outcome::Overflow => return None,
             }
-            let digits = num.ilog10() + 1;
-            if digits % 2 == 0 {
-                let t = 10u64.pow(digits / 2);
-                *current.entry(num / t).or_default() += count;
-                *current.entry(num % t).or_default() += count;
-            } else {
-                *current.entry(num * 2024).or_default() += count;
+        }
+        Some(vec![n.clone()])
+    }
+}
+
+impl<N: Number> Default for Rules<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned when stone numbers or counts don't fit in the number type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    /// Blink during which numbers overflowed, from 1
+    pub blink: u32,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "numbers overflow at blink {}", self.blink)
+    }
+}
+
+impl Error for Overflow {}
+
+/// Numbers engraved on the stones, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stones {
+    pub stones: Vec<u64>,
+}
+
+pub fn parse(input: &str) -> Result<Stones, ParseError> {
+    let stones = input
+        .split_whitespace()
+        .map(number)
+        .collect::<Result<_, _>>()?;
+    Ok(Stones { stones })
+}
+
+/// Number of stones after blinking the given number of times. The order of stones doesn't change
+/// how they evolve, so only the number of stones with each number is tracked.
+pub fn stone_count_with<N: Number>(
+    stones: &Stones,
+    blinks: u32,
+    rules: &Rules<N>,
+) -> Result<N, Overflow> {
+    let add = |counts: &mut HashMap<N, N>, stone: N, count: &N| {
+        let total = match counts.get(&stone) {
+            Some(total) => total.checked_add(count)?,
+            None => count.clone(),
+        };
+        counts.insert(stone, total);
+        Some(())
+    };
+
+    let mut counts = HashMap::new();
+    for &stone in &stones.stones {
+        add(&mut counts, N::from_u64(stone), &N::from_u64(1)).ok_or(Overflow { blink: 0 })?;
+    }
+    for blink in 1..=blinks {
+        let overflow = Overflow { blink };
+        let mut next = HashMap::new();
+        for (stone, count) in &counts {
+            for new in rules.apply(stone).ok_or(overflow.clone())? {
+                add(&mut next, new, count).ok_or(overflow.clone())?;
             }
         }
+        counts = next;
     }
+    (counts.values()).try_fold(N::from_u64(0), |total, count| {
+        total.checked_add(count).ok_or(Overflow { blink: blinks })
+    })
+}
+
+/// Number of stones after blinking the given number of times, with the puzzle's rules.
+pub fn stone_count(stones: &Stones, blinks: u32) -> Result<u64, Overflow> {
+    stone_count_with(stones, blinks, &Rules::puzzle())
+}
+
+/// Counts stones after the number of blinks set by the `blinks` context parameter, or the default.
+/// Counts use big integers if the `big` parameter is set.
+fn run(input: &str, default_blinks: u32) {
+    let stones = parse(input).unwrap();
+    let blinks = param("blinks").map_or(default_blinks, |b| b.parse().unwrap());
+    let count = if flag("big") {
+        stone_count_with::<BigUint>(&stones, blinks, &Rules::puzzle()).map(|c| c.to_string())
+    } else {
+        stone_count(&stones, blinks).map(|c| c.to_string())
+    };
+    match count {
+        Ok(count) => output!("{count}"),
+        Err(error) => output!("{error}, use big=1 for big integers"),
+    }
+}
 
-    println!("{}", steps.last().unwrap().values().sum::<u128>());
+fn part1(input: &str) {
+    run(input, 25);
+}
+
+fn part2(input: &str) {
+    run(input, 75);
 }
@@ -1,4 +1,4 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 
 const EXAMPLE: &str = "\
 RRRRIICCFF
@@ -12,7 +12,7 @@ MIIIIIJJEE
 MIIISIJEEE
 MMMISSJEEE";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Garden Groups",
     part1,
     part2,
@@ -58,34 +58,47 @@ fn dfs<F: FnMut(&mut Vec<Vec<bool>>, usize, usize)>(
     (perimeter, area)
 }
 
-fn parse(input: &str) -> (Vec<Vec<char>>, Vec<Vec<bool>>) {
-    let map: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-    let visited = vec![vec![false; map[0].len()]; map.len()];
-    (map, visited)
+/// Plant type of each garden plot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Garden {
+    pub plots: Vec<Vec<char>>,
 }
 
-fn base<F: FnMut(usize, usize) -> u32>(map: &Vec<Vec<char>>, mut f: F) {
-    let price: u32 = (0..map.len())
-        .map(|i| (0..map[0].len()).map(|j| f(i, j)).sum::<u32>())
-        .sum();
+pub fn parse(input: &str) -> Result<Garden, ParseError> {
+    let plots: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+    if plots.is_empty() {
+        return Err(ParseError::new("empty garden"));
+    }
+    if let Some(index) = plots.iter().position(|row| row.len() != plots[0].len()) {
+        return Err(ParseError::new("rows have different lengths").at_line(index));
+    }
+    Ok(Garden { plots })
+}
 
-    println!("{price}");
+fn base<F: FnMut(usize, usize) -> u32>(map: &Vec<Vec<char>>, mut f: F) -> u32 {
+    (0..map.len())
+        .map(|i| (0..map[0].len()).map(|j| f(i, j)).sum::<u32>())
+        .sum()
 }
 
-fn part1(input: &str) {
-    let (map, mut visited) = parse(input);
-    base(&map, |i, j| {
-        let (perimeter, area) = dfs(&map, &mut visited, i, j, &mut |_, _, _| ());
+/// Total price of fences, by perimeter.
+pub fn fence_price(garden: &Garden) -> u32 {
+    let map = &garden.plots;
+    let mut visited = vec![vec![false; map[0].len()]; map.len()];
+    base(map, |i, j| {
+        let (perimeter, area) = dfs(map, &mut visited, i, j, &mut |_, _, _| ());
         perimeter * area
     })
 }
 
-fn part2(input: &str) {
-    let (map, mut visited) = parse(input);
+/// Total price of fences with the bulk discount, by number of sides.
+pub fn bulk_fence_price(garden: &Garden) -> u32 {
+    let map = &garden.plots;
+    let mut visited = vec![vec![false; map[0].len()]; map.len()];
     let mut sides = vec![vec![[false; DIRECTIONS.len()]; map[0].len()]; map.len()];
-    base(&map, |i, j| {
+    base(map, |i, j| {
         let mut sides_count = 0u32;
-        let (_, area) = dfs(&map, &mut visited, i, j, &mut |visited, i, j| {
+        let (_, area) = dfs(map, &mut visited, i, j, &mut |visited, i, j| {
             for d in 0..DIRECTIONS.len() {
                 let i_next = i as i32 + DIRECTIONS[d].0;
                 let j_next = j as i32 + DIRECTIONS[d].1;
@@ -126,3 +139,11 @@ fn part2(input: &str) {
         area * sides_count
     })
 }
+
+fn part1(input: &str) {
+    output!("{}", fence_price(&parse(input).unwrap()));
+}
+
+fn part2(input: &str) {
+    output!("{}", bulk_fence_price(&parse(input).unwrap()));
+}
@@ -1,4 +1,4 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 use regex::Regex;
 
 /*
@@ -53,7 +53,7 @@ Button A: X+69, Y+23
 Button B: X+27, Y+71
 Prize: X=18641, Y=10279";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Claw Contraption",
     part1,
     part2,
@@ -61,30 +61,40 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-#[derive(Debug)]
-struct Point {
-    x: i64,
-    y: i64,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
 }
 
-#[derive(Debug)]
-struct Game {
-    button_a: Point,
-    button_b: Point,
-    prize: Point,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub button_a: Point,
+    pub button_b: Point,
+    pub prize: Point,
 }
 
-fn parse(input: &str, extra: bool) -> Vec<Game> {
+/// All claw machines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arcade {
+    pub games: Vec<Game>,
+}
+
+/// Offset of prize positions, after fixing the unit conversion error (part 2).
+pub const PRIZE_OFFSET: i64 = 10000000000000;
+
+pub fn parse(input: &str) -> Result<Arcade, ParseError> {
     let pattern = Regex::new(
         r"Button A: X\+(\d+), Y\+(\d+)\nButton B: X\+(\d+), Y\+(\d+)\nPrize: X=(\d+), Y=(\d+)",
     )
     .unwrap();
     let mut s = String::new();
     let mut games = Vec::new();
-    let extra: i64 = if extra { 10000000000000 } else { 0 };
 
     let mut process = |s: &mut String| {
-        let captures = pattern.captures(s).unwrap();
+        let Some(captures) = pattern.captures(s) else {
+            return Err(ParseError::new(format!("invalid claw machine: {s:?}")));
+        };
         let mut nums = captures
             .iter()
             .skip(1)
@@ -99,30 +109,40 @@ fn parse(input: &str, extra: bool) -> Vec<Game> {
                 y: nums.next().unwrap(),
             },
             prize: Point {
-                x: nums.next().unwrap() + extra,
-                y: nums.next().unwrap() + extra,
+                x: nums.next().unwrap(),
+                y: nums.next().unwrap(),
             },
         });
         s.clear();
+        Ok(())
     };
 
-    for line in input.lines() {
+    for (index, line) in input.lines().enumerate() {
         if line.is_empty() {
-            process(&mut s);
+            process(&mut s).map_err(|e| e.at_line(index))?;
         } else {
             s.push_str(line);
             s.push('\n');
         }
     }
-    process(&mut s);
-    games
+    process(&mut s)?;
+    Ok(Arcade { games })
 }
 
-fn solve(input: &str, extra: bool) {
-    let games = parse(input, extra);
-    let sum: i64 = games
+/// Fewest tokens needed to win all possible prizes, with prizes moved by `offset` on both axes.
+pub fn fewest_tokens(arcade: &Arcade, offset: i64) -> i64 {
+    arcade
+        .games
         .iter()
         .map(|game| {
+            let prize = Point {
+                x: game.prize.x + offset,
+                y: game.prize.y + offset,
+            };
+            let game = Game {
+                prize,
+                ..game.clone()
+            };
             let lhs = game.prize.x * game.button_b.y - game.prize.y * game.button_b.x;
             let rhs = game.button_a.x * game.button_b.y - game.button_b.x * game.button_a.y;
             if lhs % rhs == 0 {
@@ -143,14 +163,13 @@ fn solve(input: &str, extra: bool) {
                 0
             }
         })
-        .sum();
-    println!("{sum}");
+        .sum()
 }
 
 fn part1(input: &str) {
-    solve(input, false);
+    output!("{}", fewest_tokens(&parse(input).unwrap(), 0));
 }
 
 fn part2(input: &str) {
-    solve(input, true);
+    output!("{}", fewest_tokens(&parse(input).unwrap(), PRIZE_OFFSET));
 }
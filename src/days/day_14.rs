@@ -1,5 +1,7 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 use crate::api::is_example;
+use crate::render;
+use crate::render::{Palette, BLACK, GREEN};
 use regex::Regex;
 
 const EXAMPLE: &str = "\
@@ -16,7 +18,7 @@ p=7,3 v=-1,2
 p=2,4 v=2,-3
 p=9,5 v=-3,-3";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Restroom Redoubt",
     part1,
     part2,
@@ -24,61 +26,81 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-#[derive(Debug, Clone)]
-struct Robot {
-    px: i32,
-    py: i32,
-    vx: i32,
-    vy: i32,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Robot {
+    pub px: i32,
+    pub py: i32,
+    pub vx: i32,
+    pub vy: i32,
 }
 
-fn parse(input: &str) -> Vec<Robot> {
+/// Robots and the size of the area they move in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bathroom {
+    pub robots: Vec<Robot>,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Size of the area in the full puzzle (the example uses 11x7).
+pub const WIDTH: i32 = 101;
+pub const HEIGHT: i32 = 103;
+
+/// Parses the robots, for an area of the full puzzle size.
+pub fn parse(input: &str) -> Result<Bathroom, ParseError> {
     let regex = Regex::new(r"^p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)$").unwrap();
-    input
+    let robots = input
         .lines()
-        .map(|line| {
-            let captures = regex.captures(line).unwrap();
+        .enumerate()
+        .map(|(index, line)| {
+            let Some(captures) = regex.captures(line) else {
+                return Err(ParseError::new("expected p=<x>,<y> v=<x>,<y>").at_line(index));
+            };
             let mut nums = captures
                 .iter()
                 .skip(1)
                 .map(|s| s.unwrap().as_str().parse::<i32>().unwrap());
-            Robot {
+            Ok(Robot {
                 px: nums.next().unwrap(),
                 py: nums.next().unwrap(),
                 vx: nums.next().unwrap(),
                 vy: nums.next().unwrap(),
-            }
+            })
         })
-        .collect()
+        .collect::<Result<_, _>>()?;
+    Ok(Bathroom {
+        robots,
+        width: WIDTH,
+        height: HEIGHT,
+    })
 }
 
-fn map_range(mid: bool) -> (i32, i32) {
-    let max_x = if is_example() { 11 } else { 101 };
-    let max_y = if is_example() { 7 } else { 103 };
-    if mid {
-        (max_x / 2, max_y / 2)
-    } else {
-        (max_x, max_y)
+fn parse_for_mode(input: &str) -> Bathroom {
+    let mut bathroom = parse(input).unwrap();
+    if is_example() {
+        (bathroom.width, bathroom.height) = (11, 7);
     }
+    bathroom
 }
 
-fn move_robots(robots: &mut Vec<Robot>, seconds: i32) {
-    let (max_x, max_y) = map_range(false);
+fn move_robots(bathroom: &mut Bathroom, seconds: i32) {
+    let (max_x, max_y) = (bathroom.width, bathroom.height);
 
-    for robot in robots.iter_mut() {
+    for robot in bathroom.robots.iter_mut() {
         robot.px = (robot.px + robot.vx * seconds).rem_euclid(max_x);
         robot.py = (robot.py + robot.vy * seconds).rem_euclid(max_y);
     }
 }
 
-fn part1(input: &str) {
-    let mut robots = parse(input);
-    move_robots(&mut robots, 100);
+/// Product of the number of robots in each quadrant, after 100 seconds.
+pub fn safety_factor(bathroom: &Bathroom) -> u32 {
+    let mut bathroom = bathroom.clone();
+    move_robots(&mut bathroom, 100);
     // 01
     // 23
     let mut quadrants = [0u32; 4];
-    let (mid_x, mid_y) = map_range(true);
-    for robot in robots {
+    let (mid_x, mid_y) = (bathroom.width / 2, bathroom.height / 2);
+    for robot in bathroom.robots {
         let quadrant = if robot.px < mid_x {
             if robot.py < mid_y {
                 0
@@ -101,30 +123,41 @@ fn part1(input: &str) {
         quadrants[quadrant] += 1;
     }
 
-    println!("{}", quadrants.into_iter().reduce(|a, b| a * b).unwrap());
+    quadrants.into_iter().reduce(|a, b| a * b).unwrap()
 }
 
-fn part2(input: &str) {
-    let robots = parse(input);
-    let (max_x, max_y) = map_range(false);
-
-    for seconds in 0..max_x * max_y {
-        let mut robots = robots.clone();
-        move_robots(&mut robots, seconds);
-
-        // Render the map
-        let mut map = vec![vec![b'.'; max_x as usize]; max_y as usize];
-        for robot in robots.iter() {
-            map[robot.py as usize][robot.px as usize] = b'#';
-        }
+/// Map of the robots after the given number of seconds, with `#` where there are robots.
+pub fn robot_map(bathroom: &Bathroom, seconds: i32) -> Vec<String> {
+    let mut bathroom = bathroom.clone();
+    move_robots(&mut bathroom, seconds);
+
+    let mut map = vec![vec![b'.'; bathroom.width as usize]; bathroom.height as usize];
+    for robot in bathroom.robots.iter() {
+        map[robot.py as usize][robot.px as usize] = b'#';
+    }
+    map.into_iter()
+        .map(|v| String::from_utf8(v).unwrap())
+        .collect()
+}
+
+/// Fewest seconds until the robots display a Christmas tree, if they ever do.
+pub fn easter_egg_seconds(bathroom: &Bathroom) -> Option<i32> {
+    (0..bathroom.width * bathroom.height).find(|&seconds| {
         // Super rudimentary Christmas tree check
-        let map: Vec<String> = map
-            .into_iter()
-            .map(|v| String::from_utf8(v).unwrap())
-            .collect();
-        if map.iter().any(|row| row.contains("##########")) {
-            println!("{seconds}");
-            break;
-        }
+        let map = robot_map(bathroom, seconds);
+        map.iter().any(|row| row.contains("##########"))
+    })
+}
+
+fn part1(input: &str) {
+    output!("{}", safety_factor(&parse_for_mode(input)));
+}
+
+fn part2(input: &str) {
+    let bathroom = parse_for_mode(input);
+    if let Some(seconds) = easter_egg_seconds(&bathroom) {
+        let palette = Palette::new(BLACK).with(b'#', GREEN);
+        render::save_grid("day14.png", &palette, || robot_map(&bathroom, seconds));
+        output!("{seconds}");
     }
 }
@@ -1,6 +1,9 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
+use crate::player;
+use crate::render;
+use crate::render::{Animation, Frame, Palette, BLACK, GRAY, RED, YELLOW};
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Warehouse Woes",
     part1,
     part2,
@@ -397,45 +400,108 @@ mod util {
 
 use util::*;
 
-fn parse(input: &str, part2: bool) -> (Box<dyn Map>, Vec<Direction>) {
+pub use util::Direction;
+
+/// Initial warehouse map (as in the input) and the moves the robot attempts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warehouse {
+    pub map: Vec<Vec<char>>,
+    pub moves: Vec<Direction>,
+}
+
+pub fn parse(input: &str) -> Result<Warehouse, ParseError> {
     let mut map = Vec::new();
     let mut moves = Vec::new();
-    let mut lines = input.lines();
+    let mut lines = input.lines().enumerate();
+    let mut robots = 0;
 
-    loop {
-        let line = lines.next().unwrap();
+    for (index, line) in lines.by_ref() {
         if line.is_empty() {
             break;
         }
+        if let Some(c) = line.chars().find(|c| !"#.O@".contains(*c)) {
+            return Err(ParseError::new(format!("invalid tile: {c:?}")).at_line(index));
+        }
+        robots += line.matches('@').count();
         map.push(line.chars().collect());
     }
+    if robots != 1 {
+        return Err(ParseError::new("expected exactly one robot (@)"));
+    }
 
-    while let Some(line) = lines.next() {
+    for (index, line) in lines {
+        if let Some(c) = line.chars().find(|c| !"^>v<".contains(*c)) {
+            return Err(ParseError::new(format!("invalid move: {c:?}")).at_line(index));
+        }
         moves.extend(line.chars().map(Direction::from));
     }
 
-    (
-        if part2 {
-            Box::new(Map2::create(map))
-        } else {
-            Box::new(Map1::create(map))
-        },
-        moves,
-    )
+    Ok(Warehouse { map, moves })
 }
 
-fn run(input: &str, part2: bool) {
-    let (mut map, moves) = parse(input, part2);
+/// Moves the robot, recording the moves if rendering and showing them in the player if `visualise`
+/// is set. Returns the sum of GPS coordinates.
+fn run(warehouse: &Warehouse, part2: bool, visualise: bool) -> u32 {
+    let mut map: Box<dyn Map> = if part2 {
+        Box::new(Map2::create(warehouse.map.clone()))
+    } else {
+        Box::new(Map1::create(warehouse.map.clone()))
+    };
+    let moves = warehouse.moves.clone();
+    let file_name = if part2 { "day15-2.gif" } else { "day15-1.gif" };
+    let output_path = visualise.then(|| render::output_path(file_name)).flatten();
+    let mut animation = output_path.map(|path| {
+        let palette = Palette::new(BLACK)
+            .with(b'#', GRAY)
+            .with(b'@', RED)
+            .with(b'O', YELLOW)
+            .with(b'o', YELLOW)
+            .with(b'[', YELLOW)
+            .with(b']', YELLOW);
+        (path, Animation::new(palette, 5))
+    });
+    let mut record = |map: &dyn Map| {
+        if let Some((_, animation)) = &mut animation {
+            let frame = Frame::from_text(&map.to_string(), animation.palette(), render::scale());
+            animation.push(frame);
+        }
+    };
+
+    let mut moves = moves.into_iter();
+    record(&*map);
+    if visualise && player::enabled() {
+        let initial = map.to_string();
+        player::play(std::iter::once(initial).chain(moves.by_ref().map(|mv| {
+            map.move_robot(mv);
+            record(&*map);
+            map.to_string()
+        })));
+    }
+    // Moves left after quitting the player
     for mv in moves {
         map.move_robot(mv);
+        record(&*map);
+    }
+    if let Some((path, animation)) = animation {
+        render::report_error(&path, animation.save(&path));
     }
-    println!("{}", map.calc_gps());
+    map.calc_gps()
+}
+
+/// Sum of GPS coordinates of all boxes after the robot is done moving.
+pub fn box_gps_sum(warehouse: &Warehouse) -> u32 {
+    run(warehouse, false, false)
+}
+
+/// Sum of GPS coordinates of all boxes, in the scaled-up (twice as wide) warehouse.
+pub fn wide_box_gps_sum(warehouse: &Warehouse) -> u32 {
+    run(warehouse, true, false)
 }
 
 fn part1(input: &str) {
-    run(input, false);
+    output!("{}", run(&parse(input).unwrap(), false, true));
 }
 
 fn part2(input: &str) {
-    run(input, true);
+    output!("{}", run(&parse(input).unwrap(), true, true));
 }
@@ -1,4 +1,6 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
+use crate::render;
+use crate::render::{Palette, BLACK, GRAY, GREEN};
 use fix_fn::fix_fn;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashSet};
@@ -22,7 +24,7 @@ const EXAMPLE: &str = "\
 #S#.............#
 #################";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Reindeer Maze",
     part1,
     part2,
@@ -67,10 +69,18 @@ impl Ord for Entry {
     }
 }
 
-fn parse(input: &str) -> (usize, usize, usize, usize, Vec<Vec<bool>>) {
-    let (mut start_x, mut start_y) = (0, 0);
-    let (mut end_x, mut end_y) = (0, 0);
-    let vec = input
+/// Reindeer maze: walls, start (S) and end (E) tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Maze {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub walls: Vec<Vec<bool>>,
+}
+
+pub fn parse(input: &str) -> Result<Maze, ParseError> {
+    let (mut start_x, mut start_y) = (usize::MAX, 0);
+    let (mut end_x, mut end_y) = (usize::MAX, 0);
+    let walls = input
         .lines()
         .enumerate()
         .map(|(y, line)| {
@@ -89,7 +99,14 @@ fn parse(input: &str) -> (usize, usize, usize, usize, Vec<Vec<bool>>) {
                 .collect()
         })
         .collect();
-    (start_x, start_y, end_x, end_y, vec)
+    if start_x == usize::MAX || end_x == usize::MAX {
+        return Err(ParseError::new("start (S) or end (E) not found"));
+    }
+    Ok(Maze {
+        start: (start_x, start_y),
+        end: (end_x, end_y),
+        walls,
+    })
 }
 
 fn dijkstra(start_x: usize, start_y: usize, map: &Vec<Vec<bool>>) -> Vec<Vec<[u32; 4]>> {
@@ -142,16 +159,19 @@ fn dijkstra(start_x: usize, start_y: usize, map: &Vec<Vec<bool>>) -> Vec<Vec<[u3
     best
 }
 
-fn part1(input: &str) {
-    let (start_x, start_y, end_x, end_y, map) = parse(input);
-    let best = dijkstra(start_x, start_y, &map);
+/// Lowest score a reindeer could get going from start to end.
+pub fn best_score(maze: &Maze) -> u32 {
+    let ((start_x, start_y), (end_x, end_y)) = (maze.start, maze.end);
+    let best = dijkstra(start_x, start_y, &maze.walls);
 
-    println!("{}", best[end_y][end_x].into_iter().min().unwrap());
+    best[end_y][end_x].into_iter().min().unwrap()
 }
 
-fn part2(input: &str) {
-    let (start_x, start_y, end_x, end_y, map) = parse(input);
-    let best = dijkstra(start_x, start_y, &map);
+/// Tiles that are part of at least one best path.
+pub fn best_path_tiles(maze: &Maze) -> HashSet<(usize, usize)> {
+    let ((start_x, start_y), (end_x, end_y)) = (maze.start, maze.end);
+    let map = &maze.walls;
+    let best = dijkstra(start_x, start_y, map);
     let mut best_tiles = HashSet::new();
 
     let reconstruct_path = fix_fn!(|reconstruct_path,
@@ -190,5 +210,34 @@ fn part2(input: &str) {
         }
     }
 
-    println!("{}", best_tiles.len());
+    best_tiles
+}
+
+/// Number of tiles that are part of at least one best path.
+pub fn best_tiles(maze: &Maze) -> usize {
+    best_path_tiles(maze).len()
+}
+
+fn part1(input: &str) {
+    output!("{}", best_score(&parse(input).unwrap()));
+}
+
+fn part2(input: &str) {
+    let maze = parse(input).unwrap();
+    let best_tiles = best_path_tiles(&maze);
+    let palette = Palette::new(BLACK).with(b'#', GRAY).with(b'O', GREEN);
+    render::save_grid("day16.png", &palette, || {
+        (maze.walls.iter().enumerate())
+            .map(|(y, row)| {
+                (row.iter().enumerate())
+                    .map(|(x, &wall)| match wall {
+                        true => b'#',
+                        false if best_tiles.contains(&(x, y)) => b'O',
+                        false => b'.',
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<u8>>>()
+    });
+    output!("{}", best_tiles.len());
 }
@@ -1,8 +1,8 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 use regex::Regex;
 use std::fmt::Write;
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Chronospatial Computer",
     part1,
     part2,
@@ -22,20 +22,29 @@ Register C: 0
 Program: 0,3,5,4,3,0",
 };
 
-fn parse(input: &str) -> ([i64; 3], Vec<u8>) {
+/// Initial register values (A, B, C) and the program, as 3-bit numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Computer {
+    pub registers: [i64; 3],
+    pub program: Vec<u8>,
+}
+
+pub fn parse(input: &str) -> Result<Computer, ParseError> {
     let regex = Regex::new(
-        r"^Register A: (-?\d+)\s+Register B: (-?\d+)\s+Register C: (-?\d+)\s+Program: (\d(?:,\d)*)$",
+        r"^Register A: (-?\d+)\s+Register B: (-?\d+)\s+Register C: (-?\d+)\s+Program: ([0-7](?:,[0-7])*)$",
     )
     .unwrap();
-    let c = regex.captures(input.trim()).unwrap();
-    (
-        [
-            c[1].parse().unwrap(),
-            c[2].parse().unwrap(),
-            c[3].parse().unwrap(),
-        ],
-        c[4].split(',').map(|s| s.parse().unwrap()).collect(),
-    )
+    let Some(c) = regex.captures(input.trim()) else {
+        return Err(ParseError::new("expected 3 registers followed by the program"));
+    };
+    let register = |i: usize| {
+        c[i].parse()
+            .map_err(|_| ParseError::new(format!("register value out of range: {}", &c[i])))
+    };
+    Ok(Computer {
+        registers: [register(1)?, register(2)?, register(3)?],
+        program: c[4].split(',').map(|s| s.parse().unwrap()).collect(),
+    })
 }
 
 const A: usize = 0;
@@ -91,19 +100,20 @@ fn run(mut register: [i64; 3], program: &Vec<u8>) -> Vec<u8> {
     output
 }
 
-fn part1(input: &str) {
-    let (register, program) = parse(input);
-    let mut output = run(register, &program)
+/// Output of the program, as comma-separated numbers.
+pub fn program_output(computer: &Computer) -> String {
+    let mut output = run(computer.registers, &computer.program)
         .iter()
         .fold(String::new(), |mut s, n| {
             write!(s, "{n},").unwrap();
             s
         });
     output.pop(); // remove the last comma
-    println!("{output}");
+    output
 }
 
 /**
+Lowest initial value of register A that makes the program output a copy of itself.
 
 # Analyzing the example program
 
@@ -218,10 +228,8 @@ The solution uses backtracking in case there are multiple solutions at one point
 In order to work for both the example and my program, the backtracking algorithm tries to fill A with 3 bits at a time,
 this solution does not work if the program contains other shift-amounts.
  */
-fn part2(input: &str) {
-    let (register, program) = parse(input);
-
-    fn backtrack(program: &Vec<u8>, index: usize, mut register: [i64; 3], num: i64) -> bool {
+pub fn quine_register_a(computer: &Computer) -> Option<i64> {
+    fn backtrack(program: &Vec<u8>, index: usize, mut register: [i64; 3], num: i64) -> Option<i64> {
         let expected = &program[index..];
         for a in 0..8 {
             let num = num << 3 | a;
@@ -230,15 +238,25 @@ fn part2(input: &str) {
                 continue;
             }
             if index == 0 {
-                println!("{num}");
-                return true;
+                return Some(num);
             }
-            if backtrack(program, index - 1, register, num) {
-                return true;
+            if let Some(num) = backtrack(program, index - 1, register, num) {
+                return Some(num);
             }
         }
-        false
+        None
     }
 
-    backtrack(&program, program.len() - 1, register, 0);
+    let program = &computer.program;
+    backtrack(program, program.len() - 1, computer.registers, 0)
+}
+
+fn part1(input: &str) {
+    output!("{}", program_output(&parse(input).unwrap()));
+}
+
+fn part2(input: &str) {
+    if let Some(a) = quine_register_a(&parse(input).unwrap()) {
+        output!("{a}");
+    }
 }
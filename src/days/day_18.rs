@@ -1,5 +1,7 @@
-use super::DayInfo;
+use super::{number, DayInfo, ParseError};
 use crate::api::is_example;
+use crate::render;
+use crate::render::{Palette, BLACK, GRAY, RED};
 use std::collections::VecDeque;
 
 const EXAMPLE: &str = "\
@@ -29,7 +31,7 @@ const EXAMPLE: &str = "\
 1,6
 2,0";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "RAM Run",
     part1,
     part2,
@@ -37,20 +39,55 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-// Return map size and byte count, for current mode
-fn prepare() -> (Vec<Vec<bool>>, usize) {
-    let (size, byte_count) = if is_example() { (7, 12) } else { (71, 1024) };
-    let map = vec![vec![false; size]; size];
-    (map, byte_count)
+/// Positions of falling bytes, in order, with the size of the memory space and how many bytes
+/// fall before finding the path (part 1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemorySpace {
+    pub bytes: Vec<(usize, usize)>,
+    pub size: usize,
+    pub byte_count: usize,
 }
 
-fn parse<'a>(input: &'a str) -> impl Iterator<Item = (usize, usize)> + 'a {
-    input.lines().map(|line| {
-        let mut nums = line.split(',').map(|s| s.parse::<usize>().unwrap());
-        (nums.next().unwrap(), nums.next().unwrap())
+/// Parses the bytes, using the sizes of the full puzzle (the example uses 7 and 12).
+pub fn parse(input: &str) -> Result<MemorySpace, ParseError> {
+    parse_sized(input, 71, 1024)
+}
+
+/// Parses the bytes of a memory space of the given size, checking they all fall inside it.
+pub fn parse_sized(input: &str, size: usize, byte_count: usize) -> Result<MemorySpace, ParseError> {
+    let bytes = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let Some((x, y)) = line.split_once(',') else {
+                return Err(ParseError::new("expected <x>,<y>").at_line(index));
+            };
+            let (x, y) = (
+                number(x).map_err(|e| e.at_line(index))?,
+                number(y).map_err(|e| e.at_line(index))?,
+            );
+            if x >= size || y >= size {
+                let message = format!("byte {x},{y} outside the {size}x{size} memory space");
+                return Err(ParseError::new(message).at_line(index));
+            }
+            Ok((x, y))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(MemorySpace {
+        bytes,
+        size,
+        byte_count,
     })
 }
 
+fn parse_for_mode(input: &str) -> MemorySpace {
+    if is_example() {
+        parse_sized(input, 7, 12).unwrap()
+    } else {
+        parse(input).unwrap()
+    }
+}
+
 const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
 
 fn bfs(mut map: Vec<Vec<bool>>) -> Option<u32> {
@@ -77,21 +114,43 @@ fn bfs(mut map: Vec<Vec<bool>>) -> Option<u32> {
     None
 }
 
-fn part1(input: &str) {
-    let (mut map, byte_count) = prepare();
-    for (x, y) in parse(input).take(byte_count) {
+/// Minimum number of steps to reach the exit, after the first bytes have fallen.
+pub fn min_steps(space: &MemorySpace) -> Option<u32> {
+    let mut map = vec![vec![false; space.size]; space.size];
+    for &(x, y) in space.bytes.iter().take(space.byte_count) {
         map[y][x] = true;
     }
-    println!("{}", bfs(map).unwrap());
+    bfs(map)
 }
 
-fn part2(input: &str) {
-    let (mut map, ..) = prepare();
-    for (x, y) in parse(input) {
+/// Position of the first byte that cuts off the path to the exit.
+pub fn first_blocking_byte(space: &MemorySpace) -> Option<(usize, usize)> {
+    let mut map = vec![vec![false; space.size]; space.size];
+    for &(x, y) in &space.bytes {
         map[y][x] = true;
         if bfs(map.clone()).is_none() {
-            println!("{x},{y}");
-            return;
+            return Some((x, y));
         }
     }
+    None
+}
+
+fn part1(input: &str) {
+    output!("{}", min_steps(&parse_for_mode(input)).unwrap());
+}
+
+fn part2(input: &str) {
+    let space = parse_for_mode(input);
+    if let Some((x, y)) = first_blocking_byte(&space) {
+        let palette = Palette::new(BLACK).with(b'#', GRAY).with(b'X', RED);
+        render::save_grid("day18.png", &palette, || {
+            let mut grid = vec![vec![b'.'; space.size]; space.size];
+            for &(byte_x, byte_y) in space.bytes.iter().take_while(|&&byte| byte != (x, y)) {
+                grid[byte_y][byte_x] = b'#';
+            }
+            grid[y][x] = b'X';
+            grid
+        });
+        output!("{x},{y}");
+    }
 }
@@ -1,4 +1,6 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
+use crate::api::flag;
+use crate::memo::{Memo, MemoStats};
 
 const EXAMPLE: &str = "\
 r, wr, b, g, bwu, rb, gb, br
@@ -12,7 +14,7 @@ bwurrg
 brgr
 bbrgwb";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Linen Layout",
     part1,
     part2,
@@ -20,52 +22,82 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-fn parse(input: &str) -> (Vec<&str>, Vec<&str>) {
+/// Available towel patterns and the designs to make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Onsen {
+    pub towels: Vec<String>,
+    pub designs: Vec<String>,
+}
+
+pub fn parse(input: &str) -> Result<Onsen, ParseError> {
     let mut lines = input.lines();
-    let towels = lines.next().unwrap().split(", ").collect();
-    assert_eq!(lines.next().unwrap(), "");
-    let patterns = lines.collect();
-    (towels, patterns)
+    let towels = lines
+        .next()
+        .ok_or_else(|| ParseError::new("missing towel patterns"))?
+        .split(", ")
+        .map(str::to_owned)
+        .collect();
+    if lines.next() != Some("") {
+        return Err(ParseError::new("expected blank line after towel patterns").at_line(1));
+    }
+    let designs = lines.map(str::to_owned).collect();
+    Ok(Onsen { towels, designs })
 }
 
-fn solve(input: &str, part2: bool) {
-    let (towels, patterns) = parse(input);
-    let mut count = 0u64;
+/// Number of ways the end of a design can be made, sharing the cache between designs.
+fn ways<'a>(memo: &mut Memo<&'a str, u64>, towels: &[String], remaining: &'a str) -> u64 {
+    if remaining.is_empty() {
+        return 1;
+    }
+    memo.get_or_compute(remaining, |memo| {
+        (towels.iter())
+            .filter_map(|towel| remaining.strip_prefix(towel.as_str()))
+            .map(|rest| ways(memo, towels, rest))
+            .sum()
+    })
+}
 
-    'outer: for pattern in patterns {
-        // index and number of ways to reach it
-        let mut v = vec![0u64; pattern.len()];
-        v[0] = 1;
-        for index in 0..v.iter().len() {
-            if v[index] == 0 {
-                continue;
-            }
-            let remaining = &pattern[index..];
-            for &towel in &towels {
-                if remaining.starts_with(towel) {
-                    let new_index = index + towel.len();
-                    if new_index == pattern.len() {
-                        if part2 {
-                            count += v[index];
-                        } else {
-                            count += 1;
-                            continue 'outer;
-                        }
-                    } else if new_index < pattern.len() {
-                        v[new_index] += v[index];
-                    }
-                }
+/// Number of ways each design can be made, and the cache statistics.
+fn arrangements(onsen: &Onsen) -> (Vec<u64>, MemoStats) {
+    let mut memo = Memo::new();
+    let counts = (onsen.designs.iter())
+        .map(|design| {
+            if design.is_empty() {
+                0
+            } else {
+                ways(&mut memo, &onsen.towels, design)
             }
-        }
-    }
+        })
+        .collect();
+    (counts, memo.stats())
+}
+
+/// Number of designs that can be made with the available towels.
+pub fn possible_count(onsen: &Onsen) -> u64 {
+    arrangements(onsen)
+        .0
+        .iter()
+        .filter(|&&ways| ways > 0)
+        .count() as u64
+}
 
-    println!("{count}");
+/// Total number of ways the designs can be made.
+pub fn arrangement_count(onsen: &Onsen) -> u64 {
+    arrangements(onsen).0.iter().sum()
 }
 
 fn part1(input: &str) {
-    solve(input, false);
+    let (counts, stats) = arrangements(&parse(input).unwrap());
+    if flag("report") {
+        output!("{stats}");
+    }
+    output!("{}", counts.iter().filter(|&&ways| ways > 0).count());
 }
 
 fn part2(input: &str) {
-    solve(input, true);
+    let (counts, stats) = arrangements(&parse(input).unwrap());
+    if flag("report") {
+        output!("{stats}");
+    }
+    output!("{}", counts.iter().sum::<u64>());
 }
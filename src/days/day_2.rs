@@ -1,6 +1,7 @@
-use super::DayInfo;
-use std::iter::Map;
-use std::str::Lines;
+use super::{number, DayInfo, ParseError};
+use crate::api::{flag, param};
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
 
 const EXAMPLE: &str = "\
 7 6 4 2 1
@@ -10,7 +11,7 @@ const EXAMPLE: &str = "\
 8 6 4 4 1
 1 3 6 7 9";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Red-Nosed Reports",
     part1,
     part2,
@@ -18,55 +19,295 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-fn parse(input: &str) -> Map<Lines, fn(&str) -> Vec<i32>> {
-    input.lines().map(|line| {
-        line.split_whitespace()
-            .map(|word| word.parse::<i32>().unwrap())
-            .collect()
-    })
+/// Reports, each being a list of levels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reports {
+    pub reports: Vec<Vec<i32>>,
 }
 
-fn is_safe(nums: &Vec<i32>) -> bool {
-    if nums.len() < 2 {
-        return true;
+pub fn parse(input: &str) -> Result<Reports, ParseError> {
+    let reports = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            line.split_whitespace()
+                .map(number)
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.at_line(index))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Reports { reports })
+}
+
+/// Direction the levels of a safe report must follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+    /// Either increasing or decreasing, as set by the first change
+    Either,
+}
+
+impl Direction {
+    /// Signs of the changes allowed by this direction.
+    fn signs(self) -> &'static [i32] {
+        match self {
+            Direction::Increasing => &[1],
+            Direction::Decreasing => &[-1],
+            Direction::Either => &[1, -1],
+        }
     }
-    let sign = (nums[1] - nums[0]).signum();
-    if sign == 0 {
-        return false;
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Direction::Increasing => "increasing",
+            Direction::Decreasing => "decreasing",
+            Direction::Either => "either",
+        })
     }
-    let mut nums = nums.iter();
-    let mut prev = *nums.next().unwrap();
-    for &num in nums {
-        if (num - prev).signum() != sign || !(1..=3).contains(&num.abs_diff(prev)) {
-            return false;
+}
+
+/// Rules a report must respect to be safe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Allowed difference between adjacent levels, ignoring equal levels
+    pub step: RangeInclusive<i32>,
+    pub allow_equal: bool,
+    pub direction: Direction,
+}
+
+impl Rules {
+    /// Rules set by the context parameters `min_step`, `max_step`, `allow_equal` and `direction`
+    /// (`increasing`, `decreasing` or `either`), defaulting to the puzzle's rules.
+    pub fn from_params() -> Self {
+        let default = Self::default();
+        let step = |name, default| param(name).map_or(default, |s| s.parse().unwrap());
+        Self {
+            step: step("min_step", *default.step.start())..=step("max_step", *default.step.end()),
+            allow_equal: flag("allow_equal"),
+            direction: match param("direction").as_deref() {
+                None | Some("either") => Direction::Either,
+                Some("increasing") => Direction::Increasing,
+                Some("decreasing") => Direction::Decreasing,
+                Some(direction) => panic!("invalid direction: {direction}"),
+            },
         }
-        prev = num;
     }
-    true
+
+    /// Whether two adjacent levels (after removals) differ safely, in the direction of the sign.
+    fn is_safe_step(&self, a: i32, b: i32, sign: i32) -> bool {
+        let change = (b - a) * sign;
+        (change == 0 && self.allow_equal) || (change > 0 && self.step.contains(&change))
+    }
 }
 
-fn part1(input: &str) {
-    let count = parse(input)
-        .map(|nums| if is_safe(&nums) { 1 } else { 0 })
-        .sum::<u32>();
-    println!("{count}")
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            step: 1..=3,
+            allow_equal: false,
+            direction: Direction::Either,
+        }
+    }
 }
 
-fn part2(input: &str) {
-    let count = parse(input)
-        .map(|nums| {
-            if is_safe(&nums) {
-                return 1;
+/// Rule broken by a pair of adjacent levels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    Equal,
+    /// The change doesn't go in the given direction
+    WrongDirection(Direction),
+    /// The difference is out of the allowed range
+    Step(i32, RangeInclusive<i32>),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Violation::Equal => write!(f, "equal levels are not allowed"),
+            Violation::WrongDirection(direction) => write!(f, "levels must be {direction}"),
+            Violation::Step(step, range) => write!(
+                f,
+                "difference of {step} is not between {} and {}",
+                range.start(),
+                range.end()
+            ),
+        }
+    }
+}
+
+/// First pair of adjacent levels that makes a report unsafe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Offence {
+    /// Index of the first level of the pair
+    pub index: usize,
+    pub levels: (i32, i32),
+    pub violation: Violation,
+}
+
+impl Display for Offence {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "levels {} -> {} at index {}: {}",
+            self.levels.0, self.levels.1, self.index, self.violation
+        )
+    }
+}
+
+/// Checks a report against the rules, returning the first offending pair of levels if unsafe.
+pub fn check(nums: &[i32], rules: &Rules) -> Result<(), Offence> {
+    let mut direction = rules.direction;
+    for (index, pair) in nums.windows(2).enumerate() {
+        let (a, b) = (pair[0], pair[1]);
+        let offence = |violation| {
+            Err(Offence {
+                index,
+                levels: (a, b),
+                violation,
+            })
+        };
+        let change = b - a;
+        if change == 0 {
+            if !rules.allow_equal {
+                return offence(Violation::Equal);
+            }
+            continue;
+        }
+        if direction == Direction::Either {
+            direction = if change > 0 {
+                Direction::Increasing
+            } else {
+                Direction::Decreasing
+            };
+        }
+        if !direction.signs().contains(&change.signum()) {
+            return offence(Violation::WrongDirection(direction));
+        }
+        if !rules.step.contains(&change.abs()) {
+            return offence(Violation::Step(change.abs(), rules.step.clone()));
+        }
+    }
+    Ok(())
+}
+
+pub fn is_safe(nums: &[i32], rules: &Rules) -> bool {
+    check(nums, rules).is_ok()
+}
+
+/// Number of safe reports.
+pub fn safe_count(reports: &Reports, rules: &Rules) -> usize {
+    reports
+        .reports
+        .iter()
+        .filter(|nums| is_safe(nums, rules))
+        .count()
+}
+
+/// Finds the fewest levels to remove to make a report safe, removing at most `tolerance` levels.
+/// Returns the indices of the removed levels, or `None` if the report cannot be made safe.
+pub fn dampen(nums: &[i32], rules: &Rules, tolerance: usize) -> Option<Vec<usize>> {
+    (rules.direction.signs().iter())
+        .filter_map(|&sign| dampen_in_direction(nums, rules, tolerance, sign))
+        .min_by_key(Vec::len)
+}
+
+/// Same as [dampen], for levels that must all increase (sign 1) or decrease (sign -1).
+///
+/// A level can be kept after removing `r` levels before it if an earlier level, kept after removing
+/// `r - skipped` levels, is a safe step away (`skipped` being the levels in between). Only the last
+/// `tolerance + 1` levels can precede a kept level, so this takes `O(n * tolerance²)` time.
+fn dampen_in_direction(
+    nums: &[i32],
+    rules: &Rules,
+    tolerance: usize,
+    sign: i32,
+) -> Option<Vec<usize>> {
+    let n = nums.len();
+    if n == 0 {
+        return Some(Vec::new());
+    }
+
+    // For each kept level and number of levels removed before it: None if impossible, otherwise
+    // the previous kept level (None if this is the first one)
+    let mut previous: Vec<Vec<Option<Option<usize>>>> = vec![vec![None; tolerance + 1]; n];
+    for i in 0..n {
+        if i <= tolerance {
+            previous[i][i] = Some(None);
+        }
+        for j in i.saturating_sub(tolerance + 1)..i {
+            let skipped = i - j - 1;
+            if !rules.is_safe_step(nums[j], nums[i], sign) {
+                continue;
             }
-            for i in 0..nums.len() {
-                let mut nums = nums.clone();
-                nums.remove(i);
-                if is_safe(&nums) {
-                    return 1;
+            for removed in skipped..=tolerance {
+                if previous[i][removed].is_none() && previous[j][removed - skipped].is_some() {
+                    previous[i][removed] = Some(Some(j));
                 }
             }
-            0
+        }
+    }
+
+    // Levels after the last kept one are removed as well
+    let (mut last, mut removed) = (n.saturating_sub(tolerance + 1)..n)
+        .flat_map(|last| (0..=tolerance).map(move |removed| (last, removed)))
+        .filter(|&(last, removed)| {
+            removed + n - 1 - last <= tolerance && previous[last][removed].is_some()
         })
-        .sum::<u32>();
-    println!("{count}")
+        .min_by_key(|&(last, removed)| removed + n - 1 - last)?;
+
+    let mut kept = vec![false; n];
+    kept[last] = true;
+    while let Some(j) = previous[last][removed].unwrap() {
+        removed -= last - j - 1;
+        last = j;
+        kept[last] = true;
+    }
+    Some((0..n).filter(|&i| !kept[i]).collect())
+}
+
+/// Number of reports that are safe after removing at most `tolerance` levels.
+pub fn dampened_safe_count(reports: &Reports, rules: &Rules, tolerance: usize) -> usize {
+    reports
+        .reports
+        .iter()
+        .filter(|nums| dampen(nums, rules, tolerance).is_some())
+        .count()
+}
+
+/// Prints why a report is unsafe if the `explain` parameter is set.
+fn explain(index: usize, nums: &[i32], rules: &Rules) {
+    if flag("explain") {
+        if let Err(offence) = check(nums, rules) {
+            output!("report {}: {offence}", index + 1);
+        }
+    }
+}
+
+fn part1(input: &str) {
+    let reports = parse(input).unwrap();
+    let rules = Rules::from_params();
+    for (index, nums) in reports.reports.iter().enumerate() {
+        explain(index, nums, &rules);
+    }
+    output!("{}", safe_count(&reports, &rules));
+}
+
+fn part2(input: &str) {
+    let reports = parse(input).unwrap();
+    let rules = Rules::from_params();
+    let tolerance = param("tolerance").map_or(1, |s| s.parse().unwrap());
+    for (index, nums) in reports.reports.iter().enumerate() {
+        match dampen(nums, &rules, tolerance) {
+            Some(removed) if removed.is_empty() => (),
+            Some(removed) if flag("report") => {
+                output!("report {}: safe removing levels {removed:?}", index + 1)
+            }
+            Some(_) => (),
+            None => explain(index, nums, &rules),
+        }
+    }
+    output!("{}", dampened_safe_count(&reports, &rules, tolerance));
 }
@@ -1,4 +1,4 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 use crate::api::is_example;
 use std::collections::VecDeque;
 
@@ -19,7 +19,7 @@ const EXAMPLE: &str = "\
 #...#...#...###
 ###############";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Race Condition",
     part1,
     part2,
@@ -31,10 +31,18 @@ type Point = (usize, usize);
 
 const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
 
-fn parse(input: &str) -> (Point, Point, Vec<Vec<bool>>) {
-    let mut start = (0, 0);
-    let mut end = (0, 0);
-    let map = input
+/// Racetrack walls, with the start (S) and end (E) positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Racetrack {
+    pub start: Point,
+    pub end: Point,
+    pub walls: Vec<Vec<bool>>,
+}
+
+pub fn parse(input: &str) -> Result<Racetrack, ParseError> {
+    let mut start = None;
+    let mut end = None;
+    let walls = input
         .lines()
         .enumerate()
         .map(|(y, line)| {
@@ -42,16 +50,19 @@ fn parse(input: &str) -> (Point, Point, Vec<Vec<bool>>) {
                 .enumerate()
                 .map(|(x, c)| {
                     if c == 'S' {
-                        start = (x, y);
+                        start = Some((x, y));
                     } else if c == 'E' {
-                        end = (x, y);
+                        end = Some((x, y));
                     }
                     c == '#'
                 })
                 .collect()
         })
         .collect();
-    (start, end, map)
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(ParseError::new("start (S) or end (E) not found"));
+    };
+    Ok(Racetrack { start, end, walls })
 }
 
 fn bfs(map: &Vec<Vec<bool>>, start: Point) -> Vec<Vec<u32>> {
@@ -78,24 +89,11 @@ fn bfs(map: &Vec<Vec<bool>>, start: Point) -> Vec<Vec<u32>> {
     score
 }
 
-fn solve(input: &str, part2: bool) {
-    let (start, _, map) = parse(input);
-    let scores = bfs(&map, start);
-    // For main solution
-    let mut count = 0u32;
-    // For examples
-    let mut counts = vec![0u32; 100];
-
-    let cheat_distance = if part2 { 20 } else { 2 };
-    let min_cheat_value = if is_example() {
-        if part2 {
-            50
-        } else {
-            1
-        }
-    } else {
-        100
-    };
+/// Number of cheats (of up to `cheat_distance` picoseconds) by how many picoseconds they save.
+pub fn cheat_savings(track: &Racetrack, cheat_distance: i32) -> Vec<u32> {
+    let map = &track.walls;
+    let scores = bfs(map, track.start);
+    let mut counts = Vec::new();
 
     for y in 0..scores.len() as i32 {
         for x in 0..scores[0].len() as i32 {
@@ -120,37 +118,64 @@ fn solve(input: &str, part2: bool) {
                     if let Some(cheat_value) = scores[ny as usize][nx as usize].checked_sub(
                         scores[y as usize][x as usize] + x.abs_diff(nx) + y.abs_diff(ny),
                     ) {
-                        if cheat_value >= 100 {
-                            count += 1;
-                        } else if cheat_value >= min_cheat_value {
-                            counts[cheat_value as usize] += 1;
+                        if cheat_value as usize >= counts.len() {
+                            counts.resize(cheat_value as usize + 1, 0);
                         }
+                        counts[cheat_value as usize] += 1;
                     }
                 }
             }
         }
     }
 
-    if is_example() {
-        for (i, &count) in counts.iter().enumerate() {
-            if count == 0 {
-                continue;
-            }
-            if count == 1 {
-                println!("There is one cheat that saves {i} picosecond.",);
-            } else {
-                println!("There are {count} cheats that save {i} picoseconds.",);
-            }
+    counts
+}
+
+fn good_cheats(track: &Racetrack, cheat_distance: i32) -> u32 {
+    cheat_savings(track, cheat_distance)
+        .iter()
+        .skip(100)
+        .sum()
+}
+
+/// Number of 2-picosecond cheats that save at least 100 picoseconds.
+pub fn short_cheat_count(track: &Racetrack) -> u32 {
+    good_cheats(track, 2)
+}
+
+/// Number of 20-picosecond cheats that save at least 100 picoseconds.
+pub fn long_cheat_count(track: &Racetrack) -> u32 {
+    good_cheats(track, 20)
+}
+
+fn print_savings(track: &Racetrack, cheat_distance: i32, min_cheat_value: usize) {
+    let counts = cheat_savings(track, cheat_distance);
+    for (i, &count) in counts.iter().enumerate().skip(min_cheat_value) {
+        if count == 0 {
+            continue;
+        }
+        if count == 1 {
+            output!("There is one cheat that saves {i} picosecond.",);
+        } else {
+            output!("There are {count} cheats that save {i} picoseconds.",);
         }
-    } else {
-        println!("{count}");
     }
 }
 
 fn part1(input: &str) {
-    solve(input, false);
+    let track = parse(input).unwrap();
+    if is_example() {
+        print_savings(&track, 2, 1);
+    } else {
+        output!("{}", short_cheat_count(&track));
+    }
 }
 
 fn part2(input: &str) {
-    solve(input, true);
+    let track = parse(input).unwrap();
+    if is_example() {
+        print_savings(&track, 20, 50);
+    } else {
+        output!("{}", long_cheat_count(&track));
+    }
 }
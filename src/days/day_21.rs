@@ -1,4 +1,4 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 use std::collections::{HashMap, VecDeque};
 use std::fmt::{Display, Formatter};
 use std::sync::LazyLock;
@@ -10,7 +10,7 @@ const EXAMPLE: &str = "\
 456A
 379A";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Keypad Conundrum",
     part1,
     part2,
@@ -188,7 +188,28 @@ static NUMPAD_CODES: LazyLock<HashMap<(u8, u8), Code>> =
 static DIRPAD_CODES: LazyLock<HashMap<(u8, u8), Code>> =
     LazyLock::new(|| paths_to_codes(&*DIRPAD_PATHS));
 
-fn part1(input: &str) {
+/// Door codes to type, such as `029A`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoorCodes {
+    pub codes: Vec<String>,
+}
+
+pub fn parse(input: &str) -> Result<DoorCodes, ParseError> {
+    let codes = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| match line.strip_suffix('A') {
+            Some(digits) if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {
+                Ok(line.to_owned())
+            }
+            _ => Err(ParseError::new(format!("invalid door code: {line:?}")).at_line(index)),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(DoorCodes { codes })
+}
+
+/// Sum of complexities of the codes, typed through 2 robot-operated directional keypads.
+pub fn complexity_sum(codes: &DoorCodes) -> usize {
     fn directions(code: &Code) -> Code {
         match code {
             Code::Exact(str, numpad) => {
@@ -217,21 +238,20 @@ fn part1(input: &str) {
         }
     }
 
-    println!(
-        "{}",
-        input
-            .lines()
-            .map(|door_code| {
-                let robot1_code = directions(&Code::Exact(door_code.to_owned(), true));
-                let robot2_code = directions(&robot1_code);
-                let robot3_code = directions(&robot2_code);
-                Code::extract_numeric(door_code) * robot3_code.shortest_len()
-            })
-            .sum::<usize>()
-    );
+    codes
+        .codes
+        .iter()
+        .map(|door_code| {
+            let robot1_code = directions(&Code::Exact(door_code.to_owned(), true));
+            let robot2_code = directions(&robot1_code);
+            let robot3_code = directions(&robot2_code);
+            Code::extract_numeric(door_code) * robot3_code.shortest_len()
+        })
+        .sum()
 }
 
-fn part2(input: &str) {
+/// Sum of complexities of the codes, typed through 25 robot-operated directional keypads.
+pub fn long_complexity_sum(codes: &DoorCodes) -> u64 {
     fn entry_len(entry: &HashMap<&str, u64>) -> u64 {
         entry
             .iter()
@@ -303,17 +323,23 @@ fn part2(input: &str) {
             .collect()
     }
 
-    println!(
-        "{}",
-        input
-            .lines()
-            .map(|door_code| {
-                let mut directions = initial_directions(door_code);
-                for _ in 0..25 {
-                    directions = next_directions(directions);
-                }
-                Code::extract_numeric(door_code) as u64 * entry_len(&directions[0])
-            })
-            .sum::<u64>()
-    );
+    codes
+        .codes
+        .iter()
+        .map(|door_code| {
+            let mut directions = initial_directions(door_code);
+            for _ in 0..25 {
+                directions = next_directions(directions);
+            }
+            Code::extract_numeric(door_code) as u64 * entry_len(&directions[0])
+        })
+        .sum()
+}
+
+fn part1(input: &str) {
+    output!("{}", complexity_sum(&parse(input).unwrap()));
+}
+
+fn part2(input: &str) {
+    output!("{}", long_complexity_sum(&parse(input).unwrap()));
 }
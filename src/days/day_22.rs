@@ -1,7 +1,7 @@
-use super::DayInfo;
+use super::{number, DayInfo, ParseError};
 use std::collections::{HashMap, HashSet};
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Monkey Market",
     part1,
     part2,
@@ -21,8 +21,19 @@ pub const INFO: DayInfo = DayInfo {
 
 const STEPS: u32 = 2000;
 
-fn parse<'a>(input: &'a str) -> impl Iterator<Item = u64> + 'a {
-    input.lines().map(|s| s.parse().unwrap())
+/// Initial secret number of each buyer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buyers {
+    pub secrets: Vec<u64>,
+}
+
+pub fn parse(input: &str) -> Result<Buyers, ParseError> {
+    let secrets = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| number(line).map_err(|e| e.at_line(index)))
+        .collect::<Result<_, _>>()?;
+    Ok(Buyers { secrets })
 }
 
 fn next(mut secret: u64) -> u64 {
@@ -40,24 +51,29 @@ fn next(mut secret: u64) -> u64 {
     secret
 }
 
-fn part1(input: &str) {
-    println!(
-        "{}",
-        parse(input)
-            .map(|mut num| {
-                for _ in 0..STEPS {
-                    num = next(num);
-                }
-                num
-            })
-            .sum::<u64>()
-    )
+/// Sum of the 2000th secret number generated by each buyer.
+pub fn secret_sum(buyers: &Buyers) -> u64 {
+    buyers
+        .secrets
+        .iter()
+        .map(|&num| {
+            let mut num = num;
+            for _ in 0..STEPS {
+                num = next(num);
+            }
+            num
+        })
+        .sum()
 }
 
-fn part2(input: &str) {
+/// Most bananas that can be bought using a single sequence of 4 price changes.
+pub fn most_bananas(buyers: &Buyers) -> u32 {
     // Create map of diff-sequence to price locked for each num
-    let maps = parse(input)
-        .map(|mut num| {
+    let maps = buyers
+        .secrets
+        .iter()
+        .map(|&num| {
+            let mut num = num;
             // Init first 3 changes
             let mut seq = [0i8; 4];
             for i in 0..3 {
@@ -97,5 +113,13 @@ fn part2(input: &str) {
             best_total = total;
         }
     }
-    println!("{best_total}");
+    best_total
+}
+
+fn part1(input: &str) {
+    output!("{}", secret_sum(&parse(input).unwrap()));
+}
+
+fn part2(input: &str) {
+    output!("{}", most_bananas(&parse(input).unwrap()));
 }
@@ -1,4 +1,4 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 
@@ -36,7 +36,7 @@ wh-qp
 tb-vc
 td-yn";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "LAN Party",
     part1,
     part2,
@@ -44,8 +44,9 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
+/// Computer, named by two letters.
 #[derive(PartialEq, Eq, Copy, Clone, Ord, PartialOrd, Hash)]
-struct PC(u8, u8);
+pub struct PC(pub u8, pub u8);
 
 impl Debug for PC {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -59,15 +60,25 @@ impl Display for PC {
     }
 }
 
-fn parse<'a>(input: &'a str) -> impl Iterator<Item = (PC, PC)> + 'a {
-    input.lines().map(|line| {
-        let line = line.as_bytes();
-        assert_eq!(line.len(), 5);
-        let pc1 = PC(line[0], line[1]);
-        assert_eq!(line[2], b'-');
-        let pc2 = PC(line[3], line[4]);
-        (pc1, pc2)
-    })
+/// Connections between computers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    pub connections: Vec<(PC, PC)>,
+}
+
+pub fn parse(input: &str) -> Result<Network, ParseError> {
+    let connections = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let line = line.as_bytes();
+            if line.len() != 5 || line[2] != b'-' {
+                return Err(ParseError::new("expected <pc>-<pc>").at_line(index));
+            }
+            Ok((PC(line[0], line[1]), PC(line[3], line[4])))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Network { connections })
 }
 
 // Helpers
@@ -81,8 +92,9 @@ macro_rules! lan {
     }};
 }
 
-fn part1(input: &str) {
-    let connections = parse(input).collect::<HashSet<_>>();
+/// Number of sets of 3 inter-connected computers, where at least one name starts with `t`.
+pub fn t_triangle_count(network: &Network) -> usize {
+    let connections = network.connections.iter().copied().collect::<HashSet<_>>();
     let mut lan_connections: HashSet<LAN> = HashSet::new();
     for &(pc1, pc2) in &connections {
         // only keep if any PC name starts with t
@@ -105,7 +117,7 @@ fn part1(input: &str) {
             lan_connections.insert(lan!(pc1, pc2, pc3));
         }
     }
-    println!("{}", lan_connections.len());
+    lan_connections.len()
 }
 
 /*
@@ -125,10 +137,10 @@ However this worked for me as it probably finds the correct LAN starting from X.
 This correct solution is https://en.wikipedia.org/wiki/Bron%E2%80%93Kerbosch_algorithm,
 but I couldn't be bothered with it
  */
-fn part2(input: &str) {
+pub fn password(network: &Network) -> String {
     // Convert to map of PCs connected for every PC
     let mut map: HashMap<PC, HashSet<PC>> = HashMap::new();
-    for (pc1, pc2) in parse(input) {
+    for &(pc1, pc2) in &network.connections {
         // Save connection
         map.entry(pc1).or_default().insert(pc2);
         map.entry(pc2).or_default().insert(pc1);
@@ -182,10 +194,14 @@ fn part2(input: &str) {
     }
 
     best_lan.sort();
-    let mut iter = best_lan.into_iter();
-    if let Some(pc) = iter.next() {
-        print!("{pc}");
-    }
-    iter.for_each(|pc| print!(",{pc}"));
-    println!();
+    let password: Vec<String> = best_lan.iter().map(PC::to_string).collect();
+    password.join(",")
+}
+
+fn part1(input: &str) {
+    output!("{}", t_triangle_count(&parse(input).unwrap()));
+}
+
+fn part2(input: &str) {
+    output!("{}", password(&parse(input).unwrap()));
 }
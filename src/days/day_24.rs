@@ -1,11 +1,11 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 use crate::api::is_example;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Crossed Wires",
     part1,
     part2,
@@ -82,11 +82,11 @@ x05 AND y05 -> z00",
 };
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
-struct Gate<'a> {
-    input_a: &'a str,
-    input_b: &'a str,
-    output: &'a str,
-    gate_type: GateType,
+pub struct Gate<'a> {
+    pub input_a: &'a str,
+    pub input_b: &'a str,
+    pub output: &'a str,
+    pub gate_type: GateType,
 }
 
 impl<'a> Gate<'a> {
@@ -124,7 +124,7 @@ impl Display for Gate<'_> {
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
-enum GateType {
+pub enum GateType {
     AND,
     OR,
     XOR,
@@ -152,36 +152,40 @@ impl FromStr for GateType {
     }
 }
 
-#[derive(Debug)]
-struct ParseResult<'a> {
-    wires: HashSet<&'a str>,
-    initial: HashMap<&'a str, bool>,
-    gates: Vec<Gate<'a>>,
+/// Wires with their initial values, and the gates connecting them.
+#[derive(Debug, Clone)]
+pub struct Circuit<'a> {
+    pub wires: HashSet<&'a str>,
+    pub initial: HashMap<&'a str, bool>,
+    pub gates: Vec<Gate<'a>>,
 }
 
-fn parse(input: &str) -> ParseResult<'_> {
+pub fn parse(input: &str) -> Result<Circuit<'_>, ParseError> {
     let mut wires = HashSet::new();
-    let mut lines = input.lines();
+    let mut lines = input.lines().enumerate();
     let mut initial = HashMap::new();
     let mut gates = Vec::new();
 
     loop {
-        let line = lines.next().unwrap();
+        let Some((index, line)) = lines.next() else {
+            return Err(ParseError::new("missing blank line before gates"));
+        };
         if line.is_empty() {
             break;
         }
-        let mut it = line.split(": ");
-        let wire = it.next().unwrap();
-        let value = it.next().unwrap();
-        assert_eq!(it.next(), None);
-        assert!(value == "0" || value == "1");
+        let (wire, value) = match line.split_once(": ") {
+            Some((wire, value @ ("0" | "1"))) => (wire, value),
+            _ => return Err(ParseError::new("expected <wire>: <0|1>").at_line(index)),
+        };
         initial.insert(wire, value == "1");
         wires.insert(wire);
     }
 
-    let regex = Regex::new(r"(\w+) (AND|OR|XOR) (\w+) -> (\w+)").unwrap();
-    while let Some(line) = lines.next() {
-        let captures = regex.captures(line).unwrap();
+    let regex = Regex::new(r"^(\w+) (AND|OR|XOR) (\w+) -> (\w+)$").unwrap();
+    for (index, line) in lines {
+        let captures = regex.captures(line).ok_or_else(|| {
+            ParseError::new("expected <wire> <AND|OR|XOR> <wire> -> <wire>").at_line(index)
+        })?;
         let mut groups = captures.iter();
         groups.next(); // Skip the full match
         let input_a = groups.next().unwrap().unwrap().as_str();
@@ -199,11 +203,11 @@ fn parse(input: &str) -> ParseResult<'_> {
         wires.insert(output);
     }
 
-    ParseResult {
+    Ok(Circuit {
         wires,
         initial,
         gates,
-    }
+    })
 }
 
 fn form_output(values: &HashMap<&str, bool>) -> u64 {
@@ -221,18 +225,27 @@ fn form_output(values: &HashMap<&str, bool>) -> u64 {
         .unwrap()
 }
 
-fn part1(input: &str) {
-    let ParseResult {
-        initial: mut values,
-        gates: mut remaining_gates,
-        ..
-    } = parse(input);
+/// Number formed by the `z` wires once all gates have run.
+pub fn output_number(circuit: &Circuit) -> u64 {
+    let mut values = circuit.initial.clone();
+    let mut remaining_gates = circuit.gates.clone();
 
     while !remaining_gates.is_empty() {
         remaining_gates.retain(|gate| !gate.try_run(&mut values));
     }
 
-    println!("{}", form_output(&values));
+    form_output(&values)
+}
+
+fn part1(input: &str) {
+    output!("{}", output_number(&parse(input).unwrap()));
+}
+
+/// Discrepancies found by comparing a circuit with a ripple-carry adder.
+#[derive(Debug, Clone, Default)]
+pub struct AdderCheck<'a> {
+    pub errors: Vec<String>,
+    pub bad_gates: HashSet<Gate<'a>>,
 }
 
 /**
@@ -324,27 +337,11 @@ This only outputs discrepancies (errors) and potential bad gates, final results
 inspecting the rendered graph. An edge-list rendering is also provided for pasting & viewing in an online graph viewer.
 
  */
-fn part2(input: &str) {
-    if is_example() {
-        println!("This part can only run in full mode; not implemented for example");
-        return;
-    }
-
-    let ParseResult { wires, gates, .. } = parse(input);
-
-    println!("\nGraph for online viewing: https://graphonline.top/create_graph_by_edge_list");
-    let mut gate_counters = [0u32; 3];
-    for gate in &gates {
-        let gate_ident = format!(
-            "{}{}",
-            gate.gate_type, gate_counters[gate.gate_type as usize]
-        );
-        gate_counters[gate.gate_type as usize] += 1;
-        println!("{}-{gate_ident}", gate.input_a);
-        println!("{}-{gate_ident}", gate.input_b);
-        println!("{gate_ident}-{}", gate.output);
-    }
-    println!("\n");
+pub fn check_adder<'a>(circuit: &Circuit<'a>) -> AdderCheck<'a> {
+    let Circuit { wires, gates, .. } = circuit;
+    let mut check = AdderCheck::default();
+    let errors = &mut check.errors;
+    let bad_gates = &mut check.bad_gates;
 
     // Find number of output bits
     let output_bits: u8 = wires
@@ -394,14 +391,14 @@ fn part2(input: &str) {
     // Special treatment for first (incomplete) adder
     if let Some(first_xor) = find_gate!(wire!(x 0), GateType::XOR, wire!(y 0)) {
         if first_xor.output != "z00" {
-            println!("expected first XOR gate to output to z00: [{first_xor}]");
+            errors.push(format!(
+                "expected first XOR gate to output to z00: [{first_xor}]"
+            ));
         }
     } else {
-        println!("could not find first XOR gate with output to z00")
+        errors.push("could not find first XOR gate with output to z00".to_string());
     }
 
-    println!("Errors:");
-    let mut bad_gates: HashSet<Gate> = HashSet::new();
     let mut bad_carry_gate = false;
     let mut carry_gate = find_gate!(wire!(x 0), GateType::AND, wire!(y 0)).unwrap();
     for bit in 1..=bits {
@@ -412,9 +409,9 @@ fn part2(input: &str) {
         if let Some(output_xor) = find_gate!(GateType::XOR, wire!(z bit)) {
             if input_xor.output != output_xor.input_a && input_xor.output != output_xor.input_b {
                 bad_gates.insert(input_xor.clone());
-                println!(
+                errors.push(format!(
                     "expected partial input [{input_xor}] to connect to output XOR [{output_xor}]"
-                )
+                ));
             } else if bad_carry_gate {
                 if let Some(new_carry_gate) = find_gate!(
                     GateType::OR,
@@ -429,9 +426,9 @@ fn part2(input: &str) {
             }
             if carry_gate.output != output_xor.input_a && carry_gate.output != output_xor.input_b {
                 bad_gates.insert(carry_gate.clone());
-                println!(
+                errors.push(format!(
                     "expected carry out [{carry_gate}] to connect to output XOR [{output_xor}]"
-                )
+                ));
             }
         } else {
             bad_gates.insert(
@@ -441,7 +438,9 @@ fn part2(input: &str) {
                     .unwrap()
                     .clone(),
             );
-            println!("could not find output XOR gate with output z{bit:02}",);
+            errors.push(format!(
+                "could not find output XOR gate with output z{bit:02}"
+            ));
         }
 
         // check carry-out
@@ -453,25 +452,55 @@ fn part2(input: &str) {
             } else {
                 bad_gates.insert(carry_and.clone());
                 bad_gates.insert(input_and.clone());
-                println!(
+                errors.push(format!(
                     "could not find carry OR gate with inputs {} and {}",
                     carry_and.output, input_and.output
-                );
+                ));
             }
         } else {
             bad_gates.insert(carry_gate.clone());
             bad_gates.insert(input_xor.clone());
-            println!(
+            errors.push(format!(
                 "could not find carry AND gate with inputs {} and {}",
                 carry_gate.output, input_xor.output
-            );
+            ));
         }
         bad_carry_gate = true;
     }
 
-    println!("\nPossible problematic gates:");
-    for gate in bad_gates {
-        println!("*  {gate}");
+    check
+}
+
+fn part2(input: &str) {
+    if is_example() {
+        output!("This part can only run in full mode; not implemented for example");
+        return;
+    }
+
+    let circuit = parse(input).unwrap();
+
+    output!("\nGraph for online viewing: https://graphonline.top/create_graph_by_edge_list");
+    let mut gate_counters = [0u32; 3];
+    for gate in &circuit.gates {
+        let gate_ident = format!(
+            "{}{}",
+            gate.gate_type, gate_counters[gate.gate_type as usize]
+        );
+        gate_counters[gate.gate_type as usize] += 1;
+        output!("{}-{gate_ident}", gate.input_a);
+        output!("{}-{gate_ident}", gate.input_b);
+        output!("{gate_ident}-{}", gate.output);
+    }
+    output!("\n");
+
+    let check = check_adder(&circuit);
+    output!("Errors:");
+    for error in &check.errors {
+        output!("{error}");
+    }
+    output!("\nPossible problematic gates:");
+    for gate in &check.bad_gates {
+        output!("*  {gate}");
     }
-    println!();
+    output!();
 }
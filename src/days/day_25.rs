@@ -1,6 +1,6 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Code Chronicle",
     part1,
     part2,
@@ -50,22 +50,33 @@ pub const INFO: DayInfo = DayInfo {
 ...",
 };
 
-const WIDTH: usize = 5;
-const HEIGHT: usize = 7;
+pub const WIDTH: usize = 5;
+pub const HEIGHT: usize = 7;
 
-fn parse(input: &str) -> (Vec<[i32; WIDTH]>, Vec<[i32; WIDTH]>) {
-    let mut lines = input.lines().peekable();
+/// Pin heights of every key and lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schematics {
+    pub keys: Vec<[i32; WIDTH]>,
+    pub locks: Vec<[i32; WIDTH]>,
+}
+
+pub fn parse(input: &str) -> Result<Schematics, ParseError> {
+    let mut lines = input.lines().enumerate().peekable();
     let mut keys = Vec::new();
     let mut locks = Vec::new();
 
-    while let Some(&first_line) = lines.peek() {
+    while let Some(&(_, first_line)) = lines.peek() {
         let is_lock = first_line == "#".repeat(WIDTH);
         let mut new_item = [-1; WIDTH];
         for i in 1..=HEIGHT {
-            let line = lines.next().unwrap();
-            assert_eq!(line.len(), WIDTH);
-            if i == HEIGHT {
-                assert_eq!(line, if is_lock { "." } else { "#" }.repeat(WIDTH));
+            let Some((index, line)) = lines.next() else {
+                return Err(ParseError::new("incomplete schematic"));
+            };
+            if line.len() != WIDTH {
+                return Err(ParseError::new(format!("expected {WIDTH} columns")).at_line(index));
+            }
+            if i == HEIGHT && line != if is_lock { "." } else { "#" }.repeat(WIDTH) {
+                return Err(ParseError::new("unexpected last row").at_line(index));
             }
             for (index, char) in line.chars().enumerate() {
                 new_item[index] += (char == '#') as i32;
@@ -73,28 +84,34 @@ fn parse(input: &str) -> (Vec<[i32; WIDTH]>, Vec<[i32; WIDTH]>) {
         }
         if is_lock { &mut locks } else { &mut keys }.push(new_item);
 
-        if let Some(blank_line) = lines.next() {
-            assert_eq!(blank_line, "");
+        if let Some((index, blank_line)) = lines.next() {
+            if !blank_line.is_empty() {
+                return Err(ParseError::new("expected blank line").at_line(index));
+            }
         }
     }
 
-    (keys, locks)
+    Ok(Schematics { keys, locks })
 }
 
-fn part1(input: &str) {
-    let (keys, locks) = parse(input);
+/// Number of unique key/lock pairs that fit together without overlapping.
+pub fn fitting_pairs(schematics: &Schematics) -> u32 {
     let max = HEIGHT as i32 - 2;
     let mut count = 0u32;
-    for key in &keys {
-        for lock in &locks {
+    for key in &schematics.keys {
+        for lock in &schematics.locks {
             if (0..WIDTH).all(|i| key[i] + lock[i] <= max) {
                 count += 1;
             }
         }
     }
-    println!("{count}");
+    count
+}
+
+fn part1(input: &str) {
+    output!("{}", fitting_pairs(&parse(input).unwrap()));
 }
 
 fn part2(_: &str) {
-    println!("There is no part 2 for day 25 - it's over! Merry Christmas :)")
+    output!("There is no part 2 for day 25 - it's over! Merry Christmas :)")
 }
@@ -1,7 +1,10 @@
-use super::DayInfo;
-use regex::Regex;
+use super::{DayInfo, ParseError};
+use crate::api::flag;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::io::{self, ErrorKind, Read};
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Mull It Over",
     part1,
     part2,
@@ -10,33 +13,405 @@ pub const INFO: DayInfo = DayInfo {
     example2: "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))",
 };
 
-fn solve(input: &str, ignore_conditionals: bool) -> u32 {
-    let mut sum: u32 = 0;
-    let mut enabled = true;
-    for c in Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)|do\(\)|don't\(\)")
-        .unwrap()
-        .captures_iter(input)
-    {
-        match &c[0] {
-            "do()" => enabled = true,
-            "don't()" => enabled = false,
-            _ if ignore_conditionals || enabled => {
-                let mut nums = c
-                    .iter()
-                    .skip(1)
-                    .map(|m| m.unwrap().as_str().parse::<u32>().unwrap());
-                sum += nums.next().unwrap() * nums.next().unwrap();
+/// Operands accepted by an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    /// Number of 1 up to the given number of digits
+    Unsigned(usize),
+    /// Same as `Unsigned`, optionally preceded by `-`
+    Signed(usize),
+}
+
+/// State of the interpreter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub enabled: bool,
+    pub accumulator: i64,
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            accumulator: 0,
+        }
+    }
+
+    pub fn execute(&mut self, call: &Call) {
+        (call.instruction.action)(self, &call.operands)
+    }
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Effect of an instruction on the interpreter, given its operands.
+pub type Action = fn(&mut Machine, &[i64]);
+
+/// Instruction written as `name(operand,...)` in memory.
+#[derive(Debug, Clone)]
+pub struct InstructionDef {
+    pub name: &'static str,
+    pub arity: usize,
+    pub operand: Operand,
+    pub action: Action,
+}
+
+/// Instructions understood by the interpreter.
+#[derive(Debug, Clone, Default)]
+pub struct InstructionSet {
+    instructions: Vec<InstructionDef>,
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an instruction. Names must be unique, and cannot contain parentheses.
+    pub fn with(
+        mut self,
+        name: &'static str,
+        arity: usize,
+        operand: Operand,
+        action: Action,
+    ) -> Self {
+        assert!(
+            !name.is_empty() && !name.contains(['(', ')']),
+            "invalid instruction name: {name:?}"
+        );
+        assert!(
+            self.instructions
+                .iter()
+                .all(|instruction| instruction.name != name),
+            "instruction {name} registered twice"
+        );
+        self.instructions.push(InstructionDef {
+            name,
+            arity,
+            operand,
+            action,
+        });
+        self
+    }
+
+    pub fn instructions(&self) -> &[InstructionDef] {
+        &self.instructions
+    }
+
+    /// `mul(a,b)`, adding the product of two numbers of 1-3 digits to the accumulator.
+    pub fn multiply() -> Self {
+        Self::new().with("mul", 2, Operand::Unsigned(3), |machine, operands| {
+            if machine.enabled {
+                machine.accumulator += operands[0] * operands[1];
             }
-            _ => (),
+        })
+    }
+
+    /// Same as [InstructionSet::multiply], with `do()` and `don't()` enabling and disabling it.
+    pub fn conditional_multiply() -> Self {
+        Self::multiply()
+            .with("do", 0, Operand::Unsigned(0), |machine, _| {
+                machine.enabled = true
+            })
+            .with("don't", 0, Operand::Unsigned(0), |machine, _| {
+                machine.enabled = false
+            })
+    }
+}
+
+/// Instruction found in memory, with its operands.
+#[derive(Debug, Clone)]
+pub struct Call<'a> {
+    pub instruction: &'a InstructionDef,
+    pub operands: Vec<i64>,
+}
+
+impl Display for Call<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let operands: Vec<String> = self.operands.iter().map(i64::to_string).collect();
+        write!(f, "{}({})", self.instruction.name, operands.join(","))
+    }
+}
+
+/// Something found by a [Scanner], with the offset of its first byte.
+#[derive(Debug, Clone)]
+pub enum Scanned<'a> {
+    Instruction(u64, Call<'a>),
+    /// Instruction name and opening parenthesis not followed by valid operands, with the text up to
+    /// and including the first invalid byte
+    NearMiss(u64, String),
+}
+
+impl Display for Scanned<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Scanned::Instruction(offset, call) => write!(f, "{offset}: {call}"),
+            Scanned::NearMiss(offset, text) => write!(f, "{offset}: near miss {text}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum State {
+    /// Reading the name and opening parenthesis of an instruction
+    Name,
+    /// Reading the operands of an instruction, with the current operand value, its sign and digit
+    /// count
+    Operands {
+        index: usize,
+        operands: Vec<i64>,
+        value: i64,
+        negative: bool,
+        digits: usize,
+    },
+}
+
+/// State machine finding instructions in corrupted memory, reading it in fixed-size chunks.
+///
+/// Bytes are processed one at a time, so instructions split between chunks are found. When a
+/// partial instruction turns out to be invalid, scanning resumes from its second byte, to find
+/// instructions overlapping it.
+pub struct Scanner<'a, R> {
+    set: &'a InstructionSet,
+    reader: R,
+    chunk: Vec<u8>,
+    chunk_size: usize,
+    chunk_pos: usize,
+    /// Offset of the next byte to read from the reader
+    offset: u64,
+    /// Bytes to scan again after an invalid partial instruction, and the offset of the first one
+    replay: VecDeque<u8>,
+    replay_offset: u64,
+    /// Partial instruction, and the offset of its first byte
+    text: Vec<u8>,
+    start: u64,
+    state: State,
+}
+
+impl<'a, R: Read> Scanner<'a, R> {
+    pub fn new(set: &'a InstructionSet, reader: R) -> Self {
+        Self {
+            set,
+            reader,
+            chunk: Vec::new(),
+            chunk_size: 64 * 1024,
+            chunk_pos: 0,
+            offset: 0,
+            replay: VecDeque::new(),
+            replay_offset: 0,
+            text: Vec::new(),
+            start: 0,
+            state: State::Name,
+        }
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size cannot be 0");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Returns the next byte to scan and its offset.
+    fn next_byte(&mut self) -> io::Result<Option<(u64, u8)>> {
+        if let Some(byte) = self.replay.pop_front() {
+            self.replay_offset += 1;
+            return Ok(Some((self.replay_offset - 1, byte)));
+        }
+        if self.chunk_pos == self.chunk.len() {
+            self.chunk.resize(self.chunk_size, 0);
+            let len = loop {
+                match self.reader.read(&mut self.chunk) {
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    result => break result?,
+                }
+            };
+            self.chunk.truncate(len);
+            self.chunk_pos = 0;
+            if len == 0 {
+                return Ok(None);
+            }
+        }
+        self.chunk_pos += 1;
+        self.offset += 1;
+        Ok(Some((self.offset - 1, self.chunk[self.chunk_pos - 1])))
+    }
+
+    /// Drops the partial instruction, which is a near miss if its name was complete.
+    fn reject(&mut self) -> Option<Scanned<'a>> {
+        let near_miss = matches!(self.state, State::Operands { .. }).then(|| {
+            Scanned::NearMiss(self.start, String::from_utf8_lossy(&self.text).into_owned())
+        });
+        for &byte in self.text[1..].iter().rev() {
+            self.replay.push_front(byte);
+        }
+        self.replay_offset = self.start + 1;
+        self.text.clear();
+        self.state = State::Name;
+        near_miss
+    }
+
+    fn step(&mut self, offset: u64, byte: u8) -> Option<Scanned<'a>> {
+        if self.text.is_empty() {
+            self.start = offset;
+        }
+        self.text.push(byte);
+        let instructions = &self.set.instructions;
+        match &mut self.state {
+            State::Name => {
+                let name = &self.text[..self.text.len() - 1];
+                if byte == b'(' {
+                    if let Some(index) = instructions.iter().position(|i| i.name.as_bytes() == name)
+                    {
+                        self.state = State::Operands {
+                            index,
+                            operands: Vec::new(),
+                            value: 0,
+                            negative: false,
+                            digits: 0,
+                        };
+                        return None;
+                    }
+                } else if (instructions.iter()).any(|i| i.name.as_bytes().starts_with(&self.text)) {
+                    return None;
+                }
+                self.reject()
+            }
+            State::Operands {
+                index,
+                operands,
+                value,
+                negative,
+                digits,
+            } => {
+                let instruction = &instructions[*index];
+                let (max_digits, signed) = match instruction.operand {
+                    Operand::Unsigned(max_digits) => (max_digits, false),
+                    Operand::Signed(max_digits) => (max_digits, true),
+                };
+                let arity = instruction.arity;
+                let is_last = operands.len() + 1 >= arity;
+                let digit = (byte as char).to_digit(10);
+                let next_value =
+                    digit.and_then(|digit| value.checked_mul(10)?.checked_add(digit as i64));
+                match byte {
+                    b'0'..=b'9' if arity > 0 && *digits < max_digits && next_value.is_some() => {
+                        *value = next_value.unwrap();
+                        *digits += 1;
+                        None
+                    }
+                    b'-' if signed && arity > 0 && *digits == 0 && !*negative => {
+                        *negative = true;
+                        None
+                    }
+                    b',' if *digits > 0 && !is_last => {
+                        operands.push(if *negative { -*value } else { *value });
+                        (*value, *negative, *digits) = (0, false, 0);
+                        None
+                    }
+                    b')' if arity == 0 || (*digits > 0 && is_last) => {
+                        if arity > 0 {
+                            operands.push(if *negative { -*value } else { *value });
+                        }
+                        let found = Scanned::Instruction(
+                            self.start,
+                            Call {
+                                instruction,
+                                operands: std::mem::take(operands),
+                            },
+                        );
+                        self.text.clear();
+                        self.state = State::Name;
+                        Some(found)
+                    }
+                    _ => self.reject(),
+                }
+            }
+        }
+    }
+}
+
+impl<'a, R: Read> Iterator for Scanner<'a, R> {
+    type Item = io::Result<Scanned<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_byte() {
+                Err(e) => return Some(Err(e)),
+                Ok(Some((offset, byte))) => {
+                    if let Some(found) = self.step(offset, byte) {
+                        return Some(Ok(found));
+                    }
+                }
+                Ok(None) => {
+                    // The memory ends with a partial instruction
+                    if self.text.is_empty() {
+                        return None;
+                    }
+                    if let Some(found) = self.reject() {
+                        return Some(Ok(found));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Runs the instructions found in memory, read from any source.
+pub fn run<R: Read>(set: &InstructionSet, reader: R) -> io::Result<Machine> {
+    let mut machine = Machine::new();
+    for found in Scanner::new(set, reader) {
+        if let Scanned::Instruction(_, call) = found? {
+            machine.execute(&call);
+        }
+    }
+    Ok(machine)
+}
+
+/// Corrupted memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memory {
+    pub bytes: Vec<u8>,
+}
+
+pub fn parse(input: &str) -> Result<Memory, ParseError> {
+    Ok(Memory {
+        bytes: input.as_bytes().to_vec(),
+    })
+}
+
+/// Sum of all multiplications.
+pub fn mul_sum(memory: &Memory) -> i64 {
+    run(&InstructionSet::multiply(), &memory.bytes[..])
+        .unwrap()
+        .accumulator
+}
+
+/// Sum of multiplications enabled by `do()`/`don't()` instructions.
+pub fn enabled_mul_sum(memory: &Memory) -> i64 {
+    run(&InstructionSet::conditional_multiply(), &memory.bytes[..])
+        .unwrap()
+        .accumulator
+}
+
+/// Prints the offsets of instructions and near misses if the `report` parameter is set.
+fn report(set: &InstructionSet, input: &str) {
+    if flag("report") {
+        for found in Scanner::new(set, input.as_bytes()) {
+            output!("{}", found.unwrap());
         }
     }
-    sum
 }
 
 fn part1(input: &str) {
-    println!("{}", solve(input, true));
+    report(&InstructionSet::multiply(), input);
+    output!("{}", mul_sum(&parse(input).unwrap()));
 }
 
 fn part2(input: &str) {
-    println!("{}", solve(input, false));
+    report(&InstructionSet::conditional_multiply(), input);
+    output!("{}", enabled_mul_sum(&parse(input).unwrap()));
 }
@@ -1,4 +1,7 @@
-use super::DayInfo;
+use super::{DayInfo, ParseError};
+use crate::api::{flag, param};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
 
 const EXAMPLE: &str = "\
 MMMSXXMASM
@@ -12,7 +15,7 @@ SAXAMASAAA
 MAMMMXMMMM
 MXMXAXMASX";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Ceres Search",
     part1,
     part2,
@@ -20,11 +23,12 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-const WORD: &[u8] = "XMAS".as_bytes();
+const WORD: &str = "XMAS";
 
-type Direction = (i32, i32);
+/// Row and column offsets of a step in some direction.
+pub type Direction = (i32, i32);
 #[rustfmt::skip]
-const DIRECTIONS: [Direction; 8] = [
+pub const DIRECTIONS: [Direction; 8] = [
     ( 1,  0), // up
     ( 1,  1), // up-right
     ( 0,  1), // right
@@ -35,68 +39,432 @@ const DIRECTIONS: [Direction; 8] = [
     ( 1, -1), // up-left
 ];
 
-fn part1(input: &str) {
-    let map: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
-    let i_range = 0..map.len() as i32;
-    let j_range = 0..map[0].len() as i32;
-    let mut count = 0u32;
-    for i in i_range.clone() {
-        for j in j_range.clone() {
-            for direction in DIRECTIONS {
-                let mut index = 0;
-                loop {
-                    let i = i + direction.0 * index;
-                    let j = j + direction.1 * index;
-                    if !i_range.contains(&i)
-                        || !j_range.contains(&j)
-                        || map[i as usize][j as usize] != WORD[index as usize]
-                    {
-                        break;
-                    }
-                    index += 1;
-                    if index as usize == WORD.len() {
-                        count += 1;
-                        break;
+/// Compass names of the directions, north being the first row.
+const DIRECTION_NAMES: [(&str, Direction); 8] = [
+    ("n", (-1, 0)),
+    ("ne", (-1, 1)),
+    ("e", (0, 1)),
+    ("se", (1, 1)),
+    ("s", (1, 0)),
+    ("sw", (1, -1)),
+    ("w", (0, -1)),
+    ("nw", (-1, -1)),
+];
+
+pub fn direction_name(direction: Direction) -> &'static str {
+    DIRECTION_NAMES
+        .iter()
+        .find(|&&(_, d)| d == direction)
+        .map_or("?", |&(name, _)| name)
+}
+
+/// Grid of letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordSearch {
+    pub grid: Vec<Vec<u8>>,
+}
+
+pub fn parse(input: &str) -> Result<WordSearch, ParseError> {
+    let grid: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
+    if grid.is_empty() {
+        return Err(ParseError::new("empty grid"));
+    }
+    if let Some(index) = grid.iter().position(|row| row.len() != grid[0].len()) {
+        return Err(ParseError::new("rows have different lengths").at_line(index));
+    }
+    Ok(WordSearch { grid })
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: HashMap<u8, usize>,
+    /// Node of the longest proper suffix of this node's text which is in the trie
+    fail: usize,
+    /// Words ending at this node, including through its suffixes
+    words: Vec<usize>,
+}
+
+/// Aho-Corasick automaton, finding all the given words in a single pass over some text.
+#[derive(Debug, Clone)]
+struct Automaton {
+    /// Trie of the words, the root being node 0
+    nodes: Vec<Node>,
+}
+
+impl Automaton {
+    fn new(words: &[&str]) -> Self {
+        let mut nodes = vec![Node::default()];
+        for (index, word) in words.iter().enumerate() {
+            let mut node = 0;
+            for byte in word.bytes() {
+                node = match nodes[node].children.get(&byte) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.insert(byte, child);
+                        child
                     }
+                };
+            }
+            nodes[node].words.push(index);
+        }
+
+        // Suffix links, in breadth-first order so that the links of shorter texts are known
+        let mut queue: VecDeque<usize> = nodes[0].children.values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let children: Vec<(u8, usize)> =
+                nodes[node].children.iter().map(|(&b, &c)| (b, c)).collect();
+            for (byte, child) in children {
+                let mut fail = nodes[node].fail;
+                while fail != 0 && !nodes[fail].children.contains_key(&byte) {
+                    fail = nodes[fail].fail;
                 }
+                let fail = nodes[fail].children.get(&byte).copied().unwrap_or(0);
+                nodes[child].fail = fail;
+                let inherited = nodes[fail].words.clone();
+                nodes[child].words.extend(inherited);
+                queue.push_back(child);
             }
         }
+        Self { nodes }
     }
 
-    println!("{count}");
+    fn next(&self, mut node: usize, byte: u8) -> usize {
+        loop {
+            if let Some(&child) = self.nodes[node].children.get(&byte) {
+                return child;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
 }
 
-fn part2(input: &str) {
-    let map: Vec<Vec<u8>> = input.lines().map(|line| line.bytes().collect()).collect();
-    let mut count = 0u32;
-    for i in 1..(map.len() - 1) as i32 {
-        for j in 1..(map[0].len() - 1) as i32 {
-            if map[i as usize][j as usize] != b'A' {
-                continue;
+/// Options for [search].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub directions: Vec<Direction>,
+    /// Whether words can continue on the other side of the grid
+    pub wrap: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            directions: DIRECTIONS.to_vec(),
+            wrap: false,
+        }
+    }
+}
+
+/// Word found in the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'a> {
+    pub row: usize,
+    pub column: usize,
+    pub direction: Direction,
+    pub word: &'a str,
+}
+
+impl Display for Match<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at row {}, column {}, going {}",
+            self.word,
+            self.row,
+            self.column,
+            direction_name(self.direction)
+        )
+    }
+}
+
+/// Finds all occurrences of the words, reading each line of the grid in the given directions.
+pub fn search<'a>(
+    search: &WordSearch,
+    words: &[&'a str],
+    options: &SearchOptions,
+) -> Vec<Match<'a>> {
+    assert!(
+        words.iter().all(|word| !word.is_empty()),
+        "cannot search for an empty word"
+    );
+    let map = &search.grid;
+    let (height, width) = (map.len() as i32, map[0].len() as i32);
+    let contains = |(i, j): (i32, i32)| (0..height).contains(&i) && (0..width).contains(&j);
+    let automaton = Automaton::new(words);
+    let longest = words.iter().map(|word| word.len()).max().unwrap_or(0);
+
+    let mut matches = Vec::new();
+    for &direction in &options.directions {
+        let (di, dj) = direction;
+        let step = |(i, j): (i32, i32)| {
+            if options.wrap {
+                ((i + di).rem_euclid(height), (j + dj).rem_euclid(width))
+            } else {
+                (i + di, j + dj)
             }
-            for dir in 0..2 {
-                // dir is / (1) or \ (2)
-                // Find a MAS
-                let (dia, dja) = DIRECTIONS[dir * 2 + 1];
-                let (dib, djb) = DIRECTIONS[dir * 2 + 5];
-                let a = map[(i + dia) as usize][(j + dja) as usize];
-                let b = map[(i + dib) as usize][(j + djb) as usize];
-                if !(a == b'M' && b == b'S') && !(a == b'S' && b == b'M') {
+        };
+
+        // Lines of cells in this direction: rays starting at the edge, or cycles when wrapping
+        let mut lines: Vec<Vec<(i32, i32)>> = Vec::new();
+        let mut seen = vec![vec![false; width as usize]; height as usize];
+        for i in 0..height {
+            for j in 0..width {
+                if seen[i as usize][j as usize] || (!options.wrap && contains((i - di, j - dj))) {
                     continue;
                 }
-                // Find another MAS at 90 deg angle
-                let (dia, dja) = DIRECTIONS[dir * 2 + 3];
-                let (dib, djb) = DIRECTIONS[(dir * 2 + 7) % 8];
-                let a = map[(i + dia) as usize][(j + dja) as usize];
-                let b = map[(i + dib) as usize][(j + djb) as usize];
-                if !(a == b'M' && b == b'S') && !(a == b'S' && b == b'M') {
-                    continue;
+                let mut line = Vec::new();
+                let mut cell = (i, j);
+                while contains(cell) && !seen[cell.0 as usize][cell.1 as usize] {
+                    seen[cell.0 as usize][cell.1 as usize] = true;
+                    line.push(cell);
+                    cell = step(cell);
+                }
+                lines.push(line);
+            }
+        }
+
+        for line in lines {
+            // Cycles are read further, to find words going around, but only starting in the first lap
+            let len = if options.wrap {
+                line.len() + longest - 1
+            } else {
+                line.len()
+            };
+            let mut node = 0;
+            for index in 0..len {
+                let (i, j) = line[index % line.len()];
+                node = automaton.next(node, map[i as usize][j as usize]);
+                for &word in &automaton.nodes[node].words {
+                    let start = index + 1 - words[word].len();
+                    if start >= line.len() {
+                        continue;
+                    }
+                    let (row, column) = line[start];
+                    matches.push(Match {
+                        row: row as usize,
+                        column: column as usize,
+                        direction,
+                        word: words[word],
+                    });
                 }
-                count += 1;
-                break;
             }
         }
     }
+    matches
+}
 
-    println!("{count}");
+/// Number of times XMAS appears, in any direction.
+pub fn xmas_count(search: &WordSearch) -> u32 {
+    self::search(search, &[WORD], &SearchOptions::default()).len() as u32
+}
+
+/// Small 2-D template of cells, matching any letter at wildcard cells.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stencil {
+    /// Rows of letters, None being a wildcard
+    pub cells: Vec<Vec<Option<u8>>>,
+}
+
+impl Stencil {
+    /// Parses a template with rows separated by `/` or new lines, `.` being a wildcard. Spaces
+    /// around rows are ignored, for example: `M.S / .A. / M.S`.
+    pub fn parse(template: &str) -> Result<Self, ParseError> {
+        let cells: Vec<Vec<Option<u8>>> = template
+            .split(['/', '\n'])
+            .map(|row| {
+                row.trim()
+                    .bytes()
+                    .map(|cell| (cell != b'.').then_some(cell))
+                    .collect()
+            })
+            .collect();
+        if cells[0].is_empty() {
+            return Err(ParseError::new("empty stencil"));
+        }
+        if let Some(index) = cells.iter().position(|row| row.len() != cells[0].len()) {
+            return Err(ParseError::new(format!(
+                "stencil row {} has a different length",
+                index + 1
+            )));
+        }
+        Ok(Self { cells })
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells[0].len()
+    }
+
+    /// Stencil rotated by 90 degrees clockwise.
+    pub fn rotated(&self) -> Self {
+        Self {
+            cells: (0..self.width())
+                .map(|j| (0..self.height()).rev().map(|i| self.cells[i][j]).collect())
+                .collect(),
+        }
+    }
+
+    /// Stencil mirrored left to right.
+    pub fn mirrored(&self) -> Self {
+        Self {
+            cells: (self.cells.iter())
+                .map(|row| row.iter().rev().copied().collect())
+                .collect(),
+        }
+    }
+
+    /// Distinct stencils obtained by applying the symmetries, starting with this one.
+    pub fn variants(&self, symmetry: Symmetry) -> Vec<Stencil> {
+        let rotations = |stencil: &Stencil| {
+            let mut stencils = vec![stencil.clone()];
+            for _ in 0..3 {
+                stencils.push(stencils.last().unwrap().rotated());
+            }
+            stencils
+        };
+        let mirrored = self.mirrored();
+        let mut variants = match symmetry {
+            Symmetry::None => vec![self.clone()],
+            Symmetry::Rotations => rotations(self),
+            // Mirrored upside down is the left-right mirror rotated by 180 degrees
+            Symmetry::Mirrors => vec![self.clone(), mirrored.rotated().rotated(), mirrored],
+            Symmetry::All => [rotations(self), rotations(&mirrored)].concat(),
+        };
+        let mut seen = HashSet::new();
+        variants.retain(|variant| seen.insert(variant.clone()));
+        variants
+    }
+
+    fn matches_at(&self, map: &[Vec<u8>], row: usize, column: usize) -> bool {
+        (self.cells.iter().enumerate()).all(|(i, cells)| {
+            (cells.iter().enumerate())
+                .all(|(j, cell)| cell.is_none_or(|cell| map[row + i][column + j] == cell))
+        })
+    }
+}
+
+/// Transformations of a stencil to also look for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    None,
+    /// Rotations by 90, 180 and 270 degrees
+    Rotations,
+    /// Mirror images, left to right and upside down
+    Mirrors,
+    /// Rotations of the stencil and of its mirror image
+    All,
+}
+
+/// Placement of a stencil in the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilMatch {
+    /// Top-left cell of the stencil
+    pub row: usize,
+    pub column: usize,
+    /// Index of the matching stencil in [Stencil::variants]
+    pub variant: usize,
+}
+
+/// Finds all placements of the stencil, or of its variants, in the grid.
+pub fn find_stencil(
+    search: &WordSearch,
+    stencil: &Stencil,
+    symmetry: Symmetry,
+) -> Vec<StencilMatch> {
+    let map = &search.grid;
+    let mut matches = Vec::new();
+    for (variant, stencil) in stencil.variants(symmetry).iter().enumerate() {
+        if stencil.height() > map.len() || stencil.width() > map[0].len() {
+            continue;
+        }
+        for row in 0..=map.len() - stencil.height() {
+            for column in 0..=map[0].len() - stencil.width() {
+                if stencil.matches_at(map, row, column) {
+                    matches.push(StencilMatch {
+                        row,
+                        column,
+                        variant,
+                    });
+                }
+            }
+        }
+    }
+    matches
+}
+
+/// Two MAS crossing on their A, forming an X.
+const X_MAS: &str = "M.S / .A. / M.S";
+
+/// Number of MAS crosses (X-MAS).
+pub fn x_mas_count(search: &WordSearch) -> u32 {
+    let stencil = Stencil::parse(X_MAS).unwrap();
+    find_stencil(search, &stencil, Symmetry::All).len() as u32
+}
+
+/// Search options set by the `directions` (comma-separated compass names) and `wrap` parameters.
+fn options_from_params() -> SearchOptions {
+    let directions = match param("directions") {
+        None => DIRECTIONS.to_vec(),
+        Some(names) => names
+            .split(',')
+            .map(
+                |name| match DIRECTION_NAMES.iter().find(|&&(n, _)| n == name) {
+                    Some(&(_, direction)) => direction,
+                    None => panic!("invalid direction: {name}"),
+                },
+            )
+            .collect(),
+    };
+    SearchOptions {
+        directions,
+        wrap: flag("wrap"),
+    }
+}
+
+fn part1(input: &str) {
+    let search = parse(input).unwrap();
+    let words = param("words");
+    let words: Vec<&str> = match &words {
+        Some(words) => words.split(',').collect(),
+        None => vec![WORD],
+    };
+    let matches = self::search(&search, &words, &options_from_params());
+    if flag("report") {
+        for found in &matches {
+            output!("{found}");
+        }
+    }
+    output!("{}", matches.len());
+}
+
+fn part2(input: &str) {
+    let search = parse(input).unwrap();
+    let stencil = Stencil::parse(&param("stencil").unwrap_or(X_MAS.to_owned())).unwrap();
+    let symmetry = match param("symmetry").as_deref() {
+        None | Some("all") => Symmetry::All,
+        Some("none") => Symmetry::None,
+        Some("rotations") => Symmetry::Rotations,
+        Some("mirrors") => Symmetry::Mirrors,
+        Some(symmetry) => panic!("invalid symmetry: {symmetry}"),
+    };
+    let matches = find_stencil(&search, &stencil, symmetry);
+    if flag("report") {
+        for found in &matches {
+            output!(
+                "variant {} at row {}, column {}",
+                found.variant,
+                found.row,
+                found.column
+            );
+        }
+    }
+    output!("{}", matches.len());
 }
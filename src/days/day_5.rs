@@ -1,5 +1,10 @@
-use super::DayInfo;
-use std::collections::{HashMap, HashSet};
+use super::{number, DayInfo, ParseError};
+use crate::api::{flag, param};
+use crate::render;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Write};
+use std::fs;
 
 const EXAMPLE: &str = "\
 47|53
@@ -31,7 +36,7 @@ const EXAMPLE: &str = "\
 61,13,29
 97,13,75,29,47";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Print Queue",
     part1,
     part2,
@@ -39,91 +44,354 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-fn parse_rules<'a>(input: &'a str) -> (Vec<(u32, u32)>, Box<dyn 'a + Iterator<Item = Vec<u32>>>) {
-    let mut lines = input.lines();
+/// Page ordering rules (first page must come before second) and the updates to print.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintQueue {
+    pub rules: Vec<(u32, u32)>,
+    pub updates: Vec<Vec<u32>>,
+}
+
+pub fn parse(input: &str) -> Result<PrintQueue, ParseError> {
+    let mut lines = input.lines().enumerate();
     let mut rules: Vec<(u32, u32)> = Vec::new();
 
     loop {
-        let line = lines.next().unwrap();
+        let Some((index, line)) = lines.next() else {
+            return Err(ParseError::new("missing blank line before updates"));
+        };
         if line.is_empty() {
             break;
         }
-        let mut parts = line.split('|').map(|s| s.parse::<u32>().unwrap());
-        rules.push((parts.next().unwrap(), parts.next().unwrap()));
+        let Some((first, second)) = line.split_once('|') else {
+            return Err(ParseError::new("expected rule as <page>|<page>").at_line(index));
+        };
+        rules.push((
+            number(first).map_err(|e| e.at_line(index))?,
+            number(second).map_err(|e| e.at_line(index))?,
+        ));
     }
 
-    let updates = lines.map(|line| {
-        line.split(',')
-            .map(|s| s.parse().unwrap())
-            .collect::<Vec<u32>>()
-    });
+    let updates = lines
+        .map(|(index, line)| {
+            let update = line
+                .split(',')
+                .map(number)
+                .collect::<Result<Vec<u32>, _>>()
+                .map_err(|e| e.at_line(index))?;
+            let mut pages = HashSet::new();
+            match update.iter().find(|&&page| !pages.insert(page)) {
+                Some(page) => {
+                    Err(ParseError::new(format!("page {page} repeated in update")).at_line(index))
+                }
+                None => Ok(update),
+            }
+        })
+        .collect::<Result<_, _>>()?;
 
-    (rules, Box::new(updates))
+    Ok(PrintQueue { rules, updates })
 }
 
-fn check_update(update: &Vec<u32>, rules: &Vec<(u32, u32)>) -> bool {
+/// Checks that an update respects the rules, returning the first rule it breaks otherwise.
+pub fn check_update(update: &[u32], rules: &[(u32, u32)]) -> Result<(), (u32, u32)> {
     // Map of numbers to their position in the queue
     let map: HashMap<u32, u32> = HashMap::from_iter(update.iter().copied().zip(0..));
-
-    for (first, second) in rules {
-        if let Some(first_index) = map.get(first) {
-            if let Some(second_index) = map.get(second) {
+    for &(first, second) in rules {
+        if let Some(first_index) = map.get(&first) {
+            if let Some(second_index) = map.get(&second) {
                 if first_index > second_index {
-                    return false;
+                    return Err((first, second));
                 }
             }
         }
     }
-    true
+    Ok(())
 }
 
-fn part1(input: &str) {
-    let (rules, updates) = parse_rules(input);
+/// Error returned when the rules for the pages of an update contradict each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    /// Pages which must each come before the next one, and the last before the first
+    pub cycle: Vec<u32>,
+}
 
-    let count: u32 = updates
-        .map(|update| {
-            if check_update(&update, &rules) {
-                update[update.len() / 2]
-            } else {
-                0
+impl Display for CycleError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let pages: Vec<String> = self.cycle.iter().map(u32::to_string).collect();
+        write!(
+            f,
+            "rules form a cycle: {} -> {}",
+            pages.join(" -> "),
+            pages[0]
+        )
+    }
+}
+
+impl Error for CycleError {}
+
+/// Orders the pages of an update with a topological sort of the rules between them. Pages not
+/// constrained relative to each other keep their order.
+pub fn order_update(update: &[u32], rules: &[(u32, u32)]) -> Result<Vec<u32>, CycleError> {
+    // The sort works on positions in the update, so that repeated pages are sorted too
+    let mut positions: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (index, &page) in update.iter().enumerate() {
+        positions.entry(page).or_default().push(index);
+    }
+    let mut after: Vec<Vec<usize>> = vec![Vec::new(); update.len()];
+    let mut before: Vec<Vec<usize>> = vec![Vec::new(); update.len()];
+    let mut in_degree = vec![0; update.len()];
+    for (first, second) in rules {
+        let (Some(firsts), Some(seconds)) = (positions.get(first), positions.get(second)) else {
+            continue;
+        };
+        for &first in firsts {
+            for &second in seconds {
+                after[first].push(second);
+                before[second].push(first);
+                in_degree[second] += 1;
             }
-        })
-        .sum();
+        }
+    }
+
+    // Kahn's algorithm, always picking the earliest available page in the update
+    let mut available: BTreeSet<usize> = (0..update.len())
+        .filter(|&index| in_degree[index] == 0)
+        .collect();
+    let mut ordered = Vec::with_capacity(update.len());
+    while let Some(index) = available.pop_first() {
+        ordered.push(update[index]);
+        for &next in &after[index] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                available.insert(next);
+            }
+        }
+    }
+    if ordered.len() == update.len() {
+        return Ok(ordered);
+    }
 
-    println!("{count}");
+    // Every page left has a page left before it: walk back until a page repeats to find a cycle
+    let mut path: Vec<usize> = Vec::new();
+    let mut next = (0..update.len()).find(|&index| in_degree[index] > 0);
+    while let Some(index) = next {
+        if let Some(start) = path.iter().position(|&other| other == index) {
+            let cycle = path[start..].iter().rev().map(|&index| update[index]);
+            return Err(CycleError {
+                cycle: cycle.collect(),
+            });
+        }
+        path.push(index);
+        next = (before[index].iter().copied()).find(|&other| in_degree[other] > 0);
+    }
+    unreachable!("pages left unordered have a page left before them")
 }
 
-fn part2(input: &str) {
-    let (rules, updates) = parse_rules(input);
-
-    let count: u32 = updates
-        .filter(|update| !check_update(&update, &rules))
-        .map(|mut update| {
-            let all_numbers: HashSet<_> = update.iter().copied().collect();
-            let mut numbers: HashSet<u32> = HashSet::new();
-            let stop = update.len() / 2;
-            let mut index = 0;
-            loop {
-                // Find the next number that can go at the current index
-                let next_index = update
-                    .iter()
-                    .position(|&num| {
-                        rules.iter().all(|(first, second)| {
-                            *second != num
-                                || !all_numbers.contains(&first)
-                                || numbers.contains(&first)
-                        })
-                    })
-                    .unwrap();
-                if index == stop {
-                    break update[next_index];
+/// Sum of the middle pages of correctly-ordered updates.
+pub fn ordered_middle_sum(queue: &PrintQueue) -> u32 {
+    queue
+        .updates
+        .iter()
+        .filter(|update| check_update(update, &queue.rules).is_ok())
+        .map(|update| update[update.len() / 2])
+        .sum()
+}
+
+/// Sum of the middle pages of incorrectly-ordered updates, after ordering them.
+pub fn reordered_middle_sum(queue: &PrintQueue) -> Result<u32, CycleError> {
+    let mut sum = 0;
+    for update in &queue.updates {
+        if check_update(update, &queue.rules).is_err() {
+            let ordered = order_update(update, &queue.rules)?;
+            sum += ordered[ordered.len() / 2];
+        }
+    }
+    Ok(sum)
+}
+
+/// Page ordering rules as a directed graph, with an edge from each page to the pages after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleGraph {
+    pub edges: BTreeMap<u32, BTreeSet<u32>>,
+}
+
+impl RuleGraph {
+    pub fn new(rules: &[(u32, u32)]) -> Self {
+        let mut edges: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+        for &(first, second) in rules {
+            edges.entry(first).or_default().insert(second);
+            edges.entry(second).or_default();
+        }
+        Self { edges }
+    }
+
+    /// Pages reachable from a page, including itself.
+    fn reachable(&self, page: u32) -> HashSet<u32> {
+        let mut seen = HashSet::from([page]);
+        let mut stack = vec![page];
+        while let Some(page) = stack.pop() {
+            for &next in &self.edges[&page] {
+                if seen.insert(next) {
+                    stack.push(next);
                 }
-                index += 1;
-                let found_num = update.remove(next_index);
-                numbers.insert(found_num);
             }
-        })
-        .sum();
+        }
+        seen
+    }
+
+    /// Rules implied by other rules, through a chain of pages.
+    pub fn redundant_rules(&self) -> Vec<(u32, u32)> {
+        let reachable: HashMap<u32, HashSet<u32>> = (self.edges.keys())
+            .map(|&page| (page, self.reachable(page)))
+            .collect();
+        let mut redundant = Vec::new();
+        for (&first, after) in &self.edges {
+            for &second in after {
+                if (after.iter()).any(|&page| {
+                    page != first && page != second && reachable[&page].contains(&second)
+                }) {
+                    redundant.push((first, second));
+                }
+            }
+        }
+        redundant
+    }
+
+    /// Groups of pages that must all come before each other (with at least 2 pages), making a
+    /// global order of the pages impossible.
+    pub fn cycles(&self) -> Vec<Vec<u32>> {
+        // Kosaraju's algorithm: order pages by DFS finish time, then collect the pages reaching
+        // each page in reverse finish order
+        let mut finished = Vec::new();
+        let mut seen = HashSet::new();
+        for &page in self.edges.keys() {
+            if !seen.insert(page) {
+                continue;
+            }
+            let mut stack = vec![(page, self.edges[&page].iter())];
+            while let Some((page, after)) = stack.last_mut() {
+                match after.find(|&&next| seen.insert(next)) {
+                    Some(&next) => stack.push((next, self.edges[&next].iter())),
+                    None => {
+                        finished.push(*page);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        let mut before: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (&first, after) in &self.edges {
+            for &second in after {
+                before.entry(second).or_default().push(first);
+            }
+        }
+        let mut components = Vec::new();
+        let mut assigned = HashSet::new();
+        for &page in finished.iter().rev() {
+            if !assigned.insert(page) {
+                continue;
+            }
+            let mut component = vec![page];
+            let mut stack = vec![page];
+            while let Some(page) = stack.pop() {
+                for &previous in before.get(&page).into_iter().flatten() {
+                    if assigned.insert(previous) {
+                        component.push(previous);
+                        stack.push(previous);
+                    }
+                }
+            }
+            if component.len() > 1 {
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+        components
+    }
+
+    /// Graphviz DOT representation of the rules. The pages of the given update and the rules
+    /// between them are highlighted.
+    pub fn to_dot(&self, highlight: Option<&[u32]>) -> String {
+        let highlighted: HashSet<u32> = highlight.into_iter().flatten().copied().collect();
+        let mut dot = String::from("digraph rules {\n");
+        if !highlighted.is_empty() {
+            dot += "    node [color=gray];\n    edge [color=gray];\n";
+        }
+        for &page in self.edges.keys() {
+            if highlighted.contains(&page) {
+                writeln!(
+                    dot,
+                    "    {page} [color=red, style=filled, fillcolor=mistyrose];"
+                )
+                .unwrap();
+            } else {
+                writeln!(dot, "    {page};").unwrap();
+            }
+        }
+        for (first, after) in &self.edges {
+            for second in after {
+                if highlighted.contains(first) && highlighted.contains(second) {
+                    writeln!(dot, "    {first} -> {second} [color=red, penwidth=2];").unwrap();
+                } else {
+                    writeln!(dot, "    {first} -> {second};").unwrap();
+                }
+            }
+        }
+        dot + "}\n"
+    }
+}
+
+/// Pages appearing in updates, but in no rule.
+pub fn unruled_pages(queue: &PrintQueue) -> Vec<u32> {
+    let ruled: HashSet<u32> = queue.rules.iter().flat_map(|&(a, b)| [a, b]).collect();
+    let pages: BTreeSet<u32> = queue.updates.iter().flatten().copied().collect();
+    pages
+        .into_iter()
+        .filter(|page| !ruled.contains(page))
+        .collect()
+}
 
-    println!("{count}");
+/// Analyses the rules if the `report` parameter is set, and saves them as DOT if rendering, with
+/// the update given by the `highlight` parameter (starting at 1) highlighted.
+fn analyse(queue: &PrintQueue) {
+    let graph = RuleGraph::new(&queue.rules);
+    if let Some(path) = render::output_path("day5.dot") {
+        let update = param("highlight").map(|value| {
+            let update =
+                (value.parse::<usize>().ok()).and_then(|n| queue.updates.get(n.checked_sub(1)?));
+            &update.unwrap_or_else(|| panic!("invalid highlight: {value}"))[..]
+        });
+        render::report_error(&path, fs::write(&path, graph.to_dot(update)));
+    }
+    if flag("report") {
+        let format = |pages: &[u32]| {
+            let pages: Vec<String> = pages.iter().map(u32::to_string).collect();
+            if pages.is_empty() {
+                "none".to_owned()
+            } else {
+                pages.join(", ")
+            }
+        };
+        let redundant: Vec<String> = (graph.redundant_rules().iter())
+            .map(|(first, second)| format!("{first}|{second}"))
+            .collect();
+        output!("Redundant rules: {}", redundant.join(", "));
+        for cycle in graph.cycles() {
+            output!("Pages in a cycle: {}", format(&cycle));
+        }
+        output!("Pages in no rule: {}\n", format(&unruled_pages(queue)));
+    }
+}
+
+fn part1(input: &str) {
+    let queue = parse(input).unwrap();
+    analyse(&queue);
+    output!("{}", ordered_middle_sum(&queue));
+}
+
+fn part2(input: &str) {
+    match reordered_middle_sum(&parse(input).unwrap()) {
+        Ok(sum) => output!("{sum}"),
+        Err(e) => output!("{e}"),
+    }
 }
@@ -1,5 +1,10 @@
-use super::DayInfo;
-use kust::ScopeFunctions;
+use super::{DayInfo, ParseError};
+use crate::api::{flag, param};
+use crate::player;
+use crate::render;
+use crate::render::{Palette, BLACK, GRAY, YELLOW};
+use std::fmt::{Display, Formatter, Write};
+use std::thread;
 
 const EXAMPLE: &str = "\
 ....#.....
@@ -13,7 +18,7 @@ const EXAMPLE: &str = "\
 #.........
 ......#...";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Guard Gallivant",
     part1,
     part2,
@@ -21,24 +26,90 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-#[derive(Debug, Clone, Copy)]
-struct Direction {
-    id: usize,
-    x: i32,
-    y: i32,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Direction {
+    pub id: usize,
+    pub x: i32,
+    pub y: i32,
+}
+
+pub const UP: Direction = Direction { id: 0, x: 0, y: -1 };
+pub const RIGHT: Direction = Direction { id: 1, x: 1, y: 0 };
+pub const DOWN: Direction = Direction { id: 2, x: 0, y: 1 };
+pub const LEFT: Direction = Direction { id: 3, x: -1, y: 0 };
+pub const DIRECTIONS: [Direction; 4] = [UP, RIGHT, DOWN, LEFT];
+/// Glyph of a guard facing each direction
+const GLYPHS: [char; 4] = ['^', '>', 'v', '<'];
+
+impl Direction {
+    pub fn turned(self, turn: Turn) -> Direction {
+        let turns = match turn {
+            Turn::Left => DIRECTIONS.len() - 1,
+            Turn::Right => 1,
+        };
+        DIRECTIONS[(self.id + turns) % DIRECTIONS.len()]
+    }
+}
+
+/// Way a guard turns when facing a wall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+/// How tiles visited by several guards are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visited {
+    /// Tiles visited by any guard are counted once
+    Shared,
+    /// Tiles visited by each guard are counted separately
+    Separate,
 }
 
-const UP: Direction = Direction { id: 0, x: 0, y: -1 };
-const RIGHT: Direction = Direction { id: 1, x: 1, y: 0 };
-const DOWN: Direction = Direction { id: 2, x: 0, y: 1 };
-const LEFT: Direction = Direction { id: 3, x: -1, y: 0 };
-const DIRECTIONS: [Direction; 4] = [UP, RIGHT, DOWN, LEFT];
+/// Behaviour of the guards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub turn: Turn,
+    pub visited: Visited,
+}
 
-#[derive(Debug, Clone)]
-struct Tile {
-    wall: bool,
-    visited: bool,
-    visited_dir: [bool; DIRECTIONS.len()],
+impl Rules {
+    /// Rules set by the `turn` (`left` or `right`) and `visited` (`shared` or `separate`) context
+    /// parameters, defaulting to the puzzle's rules.
+    pub fn from_params() -> Self {
+        Self {
+            turn: match param("turn").as_deref() {
+                None | Some("right") => Turn::Right,
+                Some("left") => Turn::Left,
+                Some(turn) => panic!("invalid turn: {turn}"),
+            },
+            visited: match param("visited").as_deref() {
+                None | Some("shared") => Visited::Shared,
+                Some("separate") => Visited::Separate,
+                Some(visited) => panic!("invalid visited mode: {visited}"),
+            },
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            turn: Turn::Right,
+            visited: Visited::Shared,
+        }
+    }
+}
+
+/// Position and direction of a guard.
+pub type Guard = (i32, i32, Direction);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tile {
+    pub wall: bool,
+    pub visited: bool,
+    pub visited_dir: [bool; DIRECTIONS.len()],
 }
 
 impl Tile {
@@ -51,9 +122,16 @@ impl Tile {
     }
 }
 
-fn parse(input: &str) -> (i32, i32, Direction, Vec<Vec<Tile>>) {
-    let mut x_start = 0;
-    let mut y_start = 0;
+/// Map of the lab, with the starting position and direction of the guards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lab {
+    pub map: Vec<Vec<Tile>>,
+    pub guards: Vec<Guard>,
+}
+
+/// Parses the map, where guards are shown by their direction (`^`, `>`, `v` or `<`).
+pub fn parse(input: &str) -> Result<Lab, ParseError> {
+    let mut guards = Vec::new();
     let map: Vec<Vec<Tile>> = input
         .lines()
         .enumerate()
@@ -61,69 +139,485 @@ fn parse(input: &str) -> (i32, i32, Direction, Vec<Vec<Tile>>) {
             line.chars()
                 .enumerate()
                 .map(|(x, c)| {
-                    if c == '^' {
-                        x_start = x as i32;
-                        y_start = y as i32;
+                    if let Some(id) = GLYPHS.iter().position(|&glyph| glyph == c) {
+                        guards.push((x as i32, y as i32, DIRECTIONS[id]));
                     }
                     Tile::new(c == '#')
                 })
                 .collect()
         })
         .collect();
-    (x_start, y_start, UP, map)
+    if guards.is_empty() {
+        return Err(ParseError::new("no guard (^, >, v or <) found"));
+    }
+    if let Some(index) = map.iter().position(|row| row.len() != map[0].len()) {
+        return Err(ParseError::new("rows have different lengths").at_line(index));
+    }
+    Ok(Lab { map, guards })
 }
 
-fn run(map: &mut Vec<Vec<Tile>>, mut x: i32, mut y: i32, mut direction: Direction) -> bool {
-    let y_range = 0..map.len() as i32;
-    let x_range = 0..map[0].len() as i32;
+/// Walk of a guard over the map, one tile at a time.
+struct GuardWalk {
+    x: i32,
+    y: i32,
+    direction: Direction,
+    /// Directions the guard was in on each tile, to detect loops
+    seen: Vec<[bool; DIRECTIONS.len()]>,
+    /// Set once the walk ends: whether the guard got stuck in a loop
+    looped: Option<bool>,
+}
 
-    'outer: loop {
-        let current = &mut map[y as usize][x as usize];
-        if current.visited_dir[direction.id] {
-            break true;
+impl GuardWalk {
+    fn new((x, y, direction): Guard, map: &[Vec<Tile>]) -> Self {
+        Self {
+            x,
+            y,
+            direction,
+            seen: vec![[false; DIRECTIONS.len()]; map.len() * map[0].len()],
+            looped: None,
+        }
+    }
+
+    /// Moves the guard to the next tile, marking visited tiles on the map. Returns the new position
+    /// and direction it moved in, or None once the walk has ended.
+    fn step(&mut self, map: &mut [Vec<Tile>], turn: Turn) -> Option<Guard> {
+        if self.looped.is_some() {
+            return None;
+        }
+        let width = map[0].len();
+        let index = self.y as usize * width + self.x as usize;
+        if self.seen[index][self.direction.id] {
+            self.looped = Some(true);
+            return None;
         }
+        self.seen[index][self.direction.id] = true;
+        let current = &mut map[self.y as usize][self.x as usize];
         current.visited = true;
-        current.visited_dir[direction.id] = true;
+        current.visited_dir[self.direction.id] = true;
 
         // Move
-        (y, x) = loop {
-            let new_y = y + direction.y;
-            let new_x = x + direction.x;
-            if !x_range.contains(&new_x) || !y_range.contains(&new_y) {
-                break 'outer false;
+        loop {
+            let new_y = self.y + self.direction.y;
+            let new_x = self.x + self.direction.x;
+            if new_x < 0 || new_y < 0 || new_x >= width as i32 || new_y >= map.len() as i32 {
+                self.looped = Some(false);
+                return None;
             }
             if !map[new_y as usize][new_x as usize].wall {
-                break (new_y, new_x);
+                (self.x, self.y) = (new_x, new_y);
+                return Some((new_x, new_y, self.direction));
             }
-            direction = DIRECTIONS[(direction.id + 1) % DIRECTIONS.len()];
+            self.direction = self.direction.turned(turn);
+            // Being here in the new direction is the same as having turned here before
+            if self.seen[index][self.direction.id] {
+                self.looped = Some(true);
+                return None;
+            }
+            self.seen[index][self.direction.id] = true;
+            map[self.y as usize][self.x as usize].visited_dir[self.direction.id] = true;
         }
     }
 }
 
-fn part1(input: &str) {
-    let (start_x, start_y, direction, mut map) = parse(input);
-    run(&mut map, start_x, start_y, direction);
-    let visited = map.iter().flatten().filter(|t| t.visited).count();
-    println!("{visited}");
+/// Walk of all guards at the same time, one tile per step. Guards don't block each other.
+struct Walk {
+    /// One map shared by all guards, or one map per guard
+    maps: Vec<Vec<Vec<Tile>>>,
+    guards: Vec<GuardWalk>,
+    turn: Turn,
 }
 
-fn part2(input: &str) {
-    let (start_x, start_y, direction, map) = parse(input);
+impl Walk {
+    fn new(lab: &Lab, rules: &Rules) -> Self {
+        let maps = match rules.visited {
+            Visited::Shared => 1,
+            Visited::Separate => lab.guards.len(),
+        };
+        Self {
+            maps: vec![lab.map.clone(); maps],
+            guards: (lab.guards.iter())
+                .map(|&guard| GuardWalk::new(guard, &lab.map))
+                .collect(),
+            turn: rules.turn,
+        }
+    }
 
-    let initial_map = map.clone().apply(|it| run(it, start_x, start_y, direction));
+    /// Number of tiles visited, once per map.
+    fn visited_count(&self) -> usize {
+        (self.maps.iter().flatten().flatten())
+            .filter(|t| t.visited)
+            .count()
+    }
 
-    let mut count = 0u32;
-    for y in 0..map.len() as i32 {
-        for x in 0..map[0].len() as i32 {
-            if !initial_map[y as usize][x as usize].visited || (x == start_x && y == start_y) {
-                continue;
+    /// Tiles visited by any guard, in any direction.
+    fn merged_map(&self) -> Vec<Vec<Tile>> {
+        let mut merged = self.maps[0].clone();
+        for map in &self.maps[1..] {
+            for (merged, tile) in merged.iter_mut().flatten().zip(map.iter().flatten()) {
+                merged.visited |= tile.visited;
+                for (merged, &visited) in merged.visited_dir.iter_mut().zip(&tile.visited_dir) {
+                    *merged |= visited;
+                }
             }
-            let mut map = map.clone();
-            map[y as usize][x as usize].wall = true;
-            if run(&mut map, start_x, start_y, direction) {
-                count += 1;
+        }
+        merged
+    }
+}
+
+impl Iterator for Walk {
+    /// Positions of the guards that moved, and the direction they moved in
+    type Item = Vec<Guard>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let maps = self.maps.len();
+        let moved: Vec<Guard> = (self.guards.iter_mut().enumerate())
+            .filter_map(|(index, guard)| guard.step(&mut self.maps[index % maps], self.turn))
+            .collect();
+        (!moved.is_empty()).then_some(moved)
+    }
+}
+
+impl Display for Walk {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let map = self.merged_map();
+        for (y, row) in map.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let guard = (self.guards.iter()).find(|guard| {
+                    guard.looped.is_none() && (guard.x, guard.y) == (x as i32, y as i32)
+                });
+                f.write_char(if let Some(guard) = guard {
+                    GLYPHS[guard.direction.id]
+                } else if tile.wall {
+                    '#'
+                } else if tile.visited {
+                    'X'
+                } else {
+                    '.'
+                })?;
             }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws the map with the path of the guard as in the puzzle: `|` and `-` for vertical and
+/// horizontal moves, `+` where both happened (including turns), and the added obstacle as `O`.
+fn draw_path(map: &[Vec<Tile>], guards: &[Guard], obstacle: Option<(i32, i32)>) -> String {
+    let mut drawing = String::new();
+    for (y, row) in map.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            let position = Some((x as i32, y as i32));
+            let guard = guards
+                .iter()
+                .find(|guard| Some((guard.0, guard.1)) == position);
+            let vertical = tile.visited_dir[UP.id] || tile.visited_dir[DOWN.id];
+            let horizontal = tile.visited_dir[LEFT.id] || tile.visited_dir[RIGHT.id];
+            drawing.push(if position == obstacle {
+                'O'
+            } else if tile.wall {
+                '#'
+            } else if let Some(guard) = guard {
+                GLYPHS[guard.2.id]
+            } else {
+                match (vertical, horizontal) {
+                    (true, true) => '+',
+                    (true, false) => '|',
+                    (false, true) => '-',
+                    (false, false) => '.',
+                }
+            });
+        }
+        drawing.push('\n');
+    }
+    drawing
+}
+
+/// Drawing of the guards' paths, see [draw_path].
+pub fn path_drawing(lab: &Lab, rules: &Rules) -> String {
+    let mut walk = Walk::new(lab, rules);
+    walk.by_ref().for_each(drop);
+    draw_path(&walk.merged_map(), &lab.guards, None)
+}
+
+/// Whether a guard walking the map gets stuck in a loop rather than leaving it.
+fn walk_loops(guard: Guard, map: &[Vec<Tile>], turn: Turn) -> bool {
+    let mut scratch = map.to_vec();
+    let mut walk = GuardWalk::new(guard, map);
+    while walk.step(&mut scratch, turn).is_some() {}
+    walk.looped == Some(true)
+}
+
+/// Drawing of the loops guards get stuck in with an obstacle at the given position (see
+/// [draw_path]), showing only the loops themselves. As in [loop_obstacles], guards already stuck
+/// in a loop without the obstacle are left out. Returns None if no other guard loops.
+pub fn loop_drawing(lab: &Lab, rules: &Rules, obstacle: (i32, i32)) -> Option<String> {
+    let mut map = lab.map.clone();
+    map[obstacle.1 as usize][obstacle.0 as usize].wall = true;
+    let mut looped = false;
+    let mut loops = map.clone();
+    for &guard in &lab.guards {
+        if walk_loops(guard, &lab.map, rules.turn) {
+            continue;
+        }
+        let mut scratch = map.clone();
+        let mut walk = GuardWalk::new(guard, &map);
+        while walk.step(&mut scratch, rules.turn).is_some() {}
+        if walk.looped == Some(true) {
+            // The walk stops on the first repeated position & direction, walk again from there
+            // to only mark the loop
+            looped = true;
+            let mut walk = GuardWalk::new((walk.x, walk.y, walk.direction), &map);
+            while walk.step(&mut loops, rules.turn).is_some() {}
+        }
+    }
+    loops[obstacle.1 as usize][obstacle.0 as usize].wall = false;
+    looped.then(|| draw_path(&loops, &lab.guards, Some(obstacle)))
+}
+
+/// Number of tiles visited by the guards before leaving the map or getting stuck in a loop,
+/// counted once or for each guard depending on the rules.
+pub fn visited_count(lab: &Lab, rules: &Rules) -> usize {
+    let mut walk = Walk::new(lab, rules);
+    walk.by_ref().for_each(drop);
+    walk.visited_count()
+}
+
+/// For each tile and direction, where the guard stops before the next wall (or None if it leaves
+/// the map), so that it can be moved from wall to wall.
+struct JumpTable {
+    width: i32,
+    height: i32,
+    /// Stop tile of each tile, for each direction
+    stops: Vec<[Option<(i32, i32)>; DIRECTIONS.len()]>,
+}
+
+impl JumpTable {
+    fn new(map: &[Vec<Tile>]) -> Self {
+        let (width, height) = (map[0].len() as i32, map.len() as i32);
+        let mut stops = vec![[None; DIRECTIONS.len()]; (width * height) as usize];
+        for direction in DIRECTIONS {
+            // Go through tiles from the end of the direction, so that the stop of the next tile is known
+            let xs: Vec<i32> = if direction.x > 0 {
+                (0..width).rev().collect()
+            } else {
+                (0..width).collect()
+            };
+            let ys: Vec<i32> = if direction.y > 0 {
+                (0..height).rev().collect()
+            } else {
+                (0..height).collect()
+            };
+            for &y in &ys {
+                for &x in &xs {
+                    let (next_x, next_y) = (x + direction.x, y + direction.y);
+                    stops[(y * width + x) as usize][direction.id] =
+                        if next_x < 0 || next_y < 0 || next_x >= width || next_y >= height {
+                            None
+                        } else if map[next_y as usize][next_x as usize].wall {
+                            Some((x, y))
+                        } else {
+                            stops[(next_y * width + next_x) as usize][direction.id]
+                        };
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            stops,
+        }
+    }
+
+    /// Where the guard stops when moving from a tile, with an extra obstacle on the map.
+    ///
+    /// The obstacle only changes the stops of tiles in its row and column, so instead of patching
+    /// the table they are corrected here: the guard stops before the obstacle if it comes first.
+    fn stop(
+        &self,
+        x: i32,
+        y: i32,
+        direction: Direction,
+        obstacle: (i32, i32),
+    ) -> Option<(i32, i32)> {
+        let stop = self.stops[(y * self.width + x) as usize][direction.id];
+        let (obstacle_x, obstacle_y) = obstacle;
+        // Distance to the obstacle and to the stop, along the direction
+        let on_line = if direction.x == 0 {
+            obstacle_x == x
+        } else {
+            obstacle_y == y
+        };
+        let distance = (obstacle_x - x) * direction.x + (obstacle_y - y) * direction.y;
+        let stop_distance = stop.map_or(self.width.max(self.height), |(stop_x, stop_y)| {
+            (stop_x - x) * direction.x + (stop_y - y) * direction.y
+        });
+        if on_line && distance >= 1 && distance <= stop_distance {
+            Some((obstacle_x - direction.x, obstacle_y - direction.y))
+        } else {
+            stop
+        }
+    }
+
+    /// Whether the guard loops when resuming its walk from the given tile and direction, with an
+    /// extra obstacle. `seen` holds a stamp for each tile and direction the guard stopped at,
+    /// stamps different from `stamp` being from earlier walks.
+    fn loops(
+        &self,
+        (mut x, mut y, mut direction): Guard,
+        turn: Turn,
+        obstacle: (i32, i32),
+        seen: &mut [u32],
+        stamp: u32,
+    ) -> bool {
+        loop {
+            let Some(stop) = self.stop(x, y, direction, obstacle) else {
+                return false;
+            };
+            (x, y) = stop;
+            let state = (y * self.width + x) as usize * DIRECTIONS.len() + direction.id;
+            if seen[state] == stamp {
+                return true;
+            }
+            seen[state] = stamp;
+            direction = direction.turned(turn);
+        }
+    }
+}
+
+/// Positions where placing an obstacle gets a guard stuck in a loop, in the order the guards first
+/// reach them. Obstacles cannot be placed where guards start. Guards already stuck in a loop
+/// without any obstacle are ignored: an obstacle only counts if it traps a guard which would
+/// otherwise leave the lab, so there are none if every guard loops.
+///
+/// Only tiles on a guard's path can change it. The guard walks the same path until it first
+/// reaches the obstacle, so each candidate is checked by resuming the walk just before it, moving
+/// from wall to wall using a jump table. Candidates are checked in parallel.
+pub fn loop_obstacles(lab: &Lab, rules: &Rules) -> Vec<(i32, i32)> {
+    // Candidates, with the position and direction of each guard just before reaching them
+    let mut candidates: Vec<((i32, i32), Vec<Guard>)> = Vec::new();
+    let mut candidate_index = vec![vec![None; lab.map[0].len()]; lab.map.len()];
+    for &(x, y, _) in &lab.guards {
+        candidate_index[y as usize][x as usize] = Some(usize::MAX);
+    }
+    for &guard in &lab.guards {
+        let mut map = lab.map.clone();
+        let mut walk = GuardWalk::new(guard, &map);
+        let path: Vec<Guard> = std::iter::from_fn(|| walk.step(&mut map, rules.turn)).collect();
+        if walk.looped == Some(true) {
+            continue;
+        }
+        let mut reached = vec![vec![false; map[0].len()]; map.len()];
+        let (mut x, mut y) = (guard.0, guard.1);
+        for (next_x, next_y, direction) in path {
+            let (column, row) = (next_x as usize, next_y as usize);
+            if !reached[row][column] {
+                reached[row][column] = true;
+                match candidate_index[row][column] {
+                    Some(usize::MAX) => (),
+                    Some(index) => candidates[index].1.push((x, y, direction)),
+                    None => {
+                        candidate_index[row][column] = Some(candidates.len());
+                        candidates.push(((next_x, next_y), vec![(x, y, direction)]));
+                    }
+                }
+            }
+            (x, y) = (next_x, next_y);
+        }
+    }
+
+    let table = JumpTable::new(&lab.map);
+    let turn = rules.turn;
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = candidates.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let table = &table;
+                scope.spawn(move || {
+                    let mut seen =
+                        vec![0u32; (table.width * table.height) as usize * DIRECTIONS.len()];
+                    let mut stamp = 0;
+                    (chunk.iter())
+                        .filter(|(obstacle, resumes)| {
+                            resumes.iter().any(|&resume| {
+                                stamp += 1;
+                                table.loops(resume, turn, *obstacle, &mut seen, stamp)
+                            })
+                        })
+                        .map(|(obstacle, _)| *obstacle)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Number of positions where placing an obstacle gets a guard stuck in a loop (see
+/// [loop_obstacles]).
+pub fn loop_obstacle_count(lab: &Lab, rules: &Rules) -> u32 {
+    loop_obstacles(lab, rules).len() as u32
+}
+
+fn part1(input: &str) {
+    let lab = parse(input).unwrap();
+    let rules = Rules::from_params();
+    if flag("report") {
+        output!("{}", path_drawing(&lab, &rules));
+    }
+    let mut walk = Walk::new(&lab, &rules);
+    if player::enabled() {
+        let mut started = false;
+        player::play(std::iter::from_fn(|| {
+            if started {
+                walk.next()?;
+            }
+            started = true;
+            Some(walk.to_string())
+        }));
+    }
+    // Steps left after quitting the player
+    walk.by_ref().for_each(drop);
+    let palette = Palette::new(BLACK).with(b'#', GRAY).with(b'X', YELLOW);
+    render::save_grid("day6.png", &palette, || {
+        (walk.merged_map().iter())
+            .map(|row| {
+                (row.iter())
+                    .map(|tile| match tile {
+                        Tile { wall: true, .. } => b'#',
+                        Tile { visited: true, .. } => b'X',
+                        _ => b'.',
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<u8>>>()
+    });
+    output!("{}", walk.visited_count());
+}
+
+fn part2(input: &str) {
+    let lab = parse(input).unwrap();
+    let rules = Rules::from_params();
+    if let Some(candidate) = param("candidate") {
+        let (x, y) = candidate
+            .split_once(',')
+            .expect("candidate must be <x>,<y>");
+        let obstacle = (x.parse().unwrap(), y.parse().unwrap());
+        match loop_drawing(&lab, &rules, obstacle) {
+            Some(drawing) => output!("{drawing}"),
+            None => output!("No loop with an obstacle at {x},{y}\n"),
+        }
+    }
+    let obstacles = loop_obstacles(&lab, &rules);
+    if flag("report") {
+        for (x, y) in &obstacles {
+            output!("{x},{y}");
         }
     }
-    println!("{count}");
+    output!("{}", obstacles.len());
 }
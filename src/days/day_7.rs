@@ -1,4 +1,7 @@
-use super::DayInfo;
+use super::{number, DayInfo, ParseError};
+use crate::api::{flag, param};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Write};
 
 const EXAMPLE: &str = "\
 190: 10 19
@@ -11,7 +14,7 @@ const EXAMPLE: &str = "\
 21037: 9 7 18 13
 292: 11 6 16 20";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Bridge Repair",
     part1,
     part2,
@@ -19,56 +22,559 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-fn parse_line(line: &str) -> (u64, Vec<u64>) {
-    let mut parts = line.split(": ");
-    let result = parts.next().unwrap().parse().unwrap();
-    let nums = parts
-        .next()
-        .unwrap()
-        .split(' ')
-        .map(|v| v.parse().unwrap())
-        .collect();
-    (result, nums)
-}
-
-fn can_solve(result: u64, first: u64, rest: &[u64], allow_concat: bool) -> bool {
-    if rest.len() == 0 {
-        result == first
-    } else {
-        can_solve(result, first + rest[0], &rest[1..], allow_concat)
-            || can_solve(result, first * rest[0], &rest[1..], allow_concat)
-            || if allow_concat {
-                can_solve(
-                    result,
-                    first * 10u64.pow(rest[0].ilog10() + 1) + rest[0],
+/// Test value and the numbers that should be combined to obtain it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    pub result: u64,
+    pub nums: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calibration {
+    pub equations: Vec<Equation>,
+}
+
+fn parse_line(line: &str) -> Result<Equation, ParseError> {
+    let Some((result, nums)) = line.split_once(": ") else {
+        return Err(ParseError::new("expected <result>: <numbers>"));
+    };
+    let result = number(result)?;
+    let nums: Vec<u64> = nums.split(' ').map(number).collect::<Result<_, _>>()?;
+    if nums.is_empty() {
+        return Err(ParseError::new("equation has no numbers"));
+    }
+    Ok(Equation { result, nums })
+}
+
+pub fn parse(input: &str) -> Result<Calibration, ParseError> {
+    let equations = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| parse_line(line).map_err(|e| e.at_line(index)))
+        .collect::<Result<_, _>>()?;
+    Ok(Calibration { equations })
+}
+
+/// Left operands for which an operation gives a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inverse {
+    /// No left operand gives the result
+    None,
+    /// Only this left operand gives the result
+    Unique(u64),
+    /// Several left operands give the result (such as any number times 0)
+    Ambiguous,
+}
+
+/// Binary operator. Operators never give a result smaller than their operands, unless an operand
+/// is 0.
+#[derive(Debug, Clone, Copy)]
+pub struct Operator {
+    pub symbol: &'static str,
+    /// Operators with a higher precedence are applied first, when evaluating with precedence
+    pub precedence: u8,
+    /// Result of `left op right`, or None if it overflows
+    pub apply: fn(u64, u64) -> Option<u64>,
+    /// Left operands such that `left op right` gives the result
+    pub invert: fn(u64, u64) -> Inverse,
+}
+
+impl From<Option<u64>> for Inverse {
+    fn from(left: Option<u64>) -> Self {
+        left.map_or(Inverse::None, Inverse::Unique)
+    }
+}
+
+/// Power of 10 above the given number, or None if it overflows
+fn decimal_shift(n: u64) -> Option<u64> {
+    10u64.checked_pow(n.checked_ilog10().unwrap_or(0) + 1)
+}
+
+pub const ADD: Operator = Operator {
+    symbol: "+",
+    precedence: 1,
+    apply: |left, right| left.checked_add(right),
+    invert: |result, right| result.checked_sub(right).into(),
+};
+
+pub const MULTIPLY: Operator = Operator {
+    symbol: "*",
+    precedence: 2,
+    apply: |left, right| left.checked_mul(right),
+    invert: |result, right| match (result, right) {
+        (0, 0) => Inverse::Ambiguous,
+        (_, 0) => Inverse::None,
+        _ => (result % right == 0).then(|| result / right).into(),
+    },
+};
+
+pub const CONCAT: Operator = Operator {
+    symbol: "||",
+    precedence: 3,
+    apply: |left, right| left.checked_mul(decimal_shift(right)?)?.checked_add(right),
+    invert: |result, right| match decimal_shift(right) {
+        Some(shift) => (result % shift == right).then(|| result / shift).into(),
+        None => Inverse::None,
+    },
+};
+
+/// Operators that can be placed between numbers.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorSet {
+    operators: Vec<Operator>,
+}
+
+impl OperatorSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an operator. Symbols must be unique.
+    pub fn with(mut self, operator: Operator) -> Self {
+        assert!(
+            self.operators.iter().all(|o| o.symbol != operator.symbol),
+            "duplicate operator {}",
+            operator.symbol
+        );
+        self.operators.push(operator);
+        self
+    }
+
+    /// `+` and `*`, for part 1.
+    pub fn basic() -> Self {
+        Self::new().with(ADD).with(MULTIPLY)
+    }
+
+    /// `+`, `*` and `||`, for part 2.
+    pub fn with_concat() -> Self {
+        Self::basic().with(CONCAT)
+    }
+
+    /// Set of the operators with the given comma separated symbols, among `+`, `*` and `||`.
+    pub fn from_symbols(symbols: &str) -> Option<Self> {
+        symbols.split(',').try_fold(Self::new(), |set, symbol| {
+            let operator = [ADD, MULTIPLY, CONCAT]
+                .into_iter()
+                .find(|o| o.symbol == symbol.trim())?;
+            Some(set.with(operator))
+        })
+    }
+
+    /// Changes the precedence of an operator.
+    pub fn with_precedence(mut self, symbol: &str, precedence: u8) -> Self {
+        for operator in &mut self.operators {
+            if operator.symbol == symbol {
+                operator.precedence = precedence;
+            }
+        }
+        self
+    }
+
+    /// Set chosen with the `operators` context parameter, or the given default. The precedence of
+    /// `||` can be changed with the `concat_precedence` parameter.
+    pub fn from_params(default: Self) -> Self {
+        let set = match param("operators") {
+            Some(symbols) => Self::from_symbols(&symbols)
+                .unwrap_or_else(|| panic!("invalid operators: {symbols}")),
+            None => default,
+        };
+        match param("concat_precedence") {
+            Some(precedence) => set.with_precedence(CONCAT.symbol, precedence.parse().unwrap()),
+            None => set,
+        }
+    }
+
+    pub fn operators(&self) -> &[Operator] {
+        &self.operators
+    }
+}
+
+/// Operators making an equation true, in order.
+#[derive(Debug, Clone)]
+pub struct Solution<'a> {
+    pub equation: &'a Equation,
+    pub operators: Vec<Operator>,
+}
+
+impl Solution<'_> {
+    /// Numbers and operators, such as `81 + 40 * 27`.
+    pub fn expression(&self) -> String {
+        let mut expression = self.equation.nums[0].to_string();
+        for (operator, num) in self.operators.iter().zip(&self.equation.nums[1..]) {
+            write!(expression, " {} {num}", operator.symbol).unwrap();
+        }
+        expression
+    }
+}
+
+impl Display for Solution<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{} = {}", self.equation.result, self.expression())
+    }
+}
+
+/// Order in which operators are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evaluation {
+    /// Strictly left to right, as in the puzzle
+    LeftToRight,
+    /// Operators with a higher precedence first, then left to right
+    Precedence,
+}
+
+impl Evaluation {
+    /// Evaluation set by the `evaluation` context parameter (`left_to_right` or `precedence`),
+    /// defaulting to the puzzle's.
+    pub fn from_params() -> Self {
+        match param("evaluation").as_deref() {
+            None | Some("left_to_right") => Evaluation::LeftToRight,
+            Some("precedence") => Evaluation::Precedence,
+            Some(evaluation) => panic!("invalid evaluation: {evaluation}"),
+        }
+    }
+}
+
+/// Error returned when evaluating an equation's numbers doesn't fit in 64 bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub result: u64,
+    /// Expression that overflowed, numbers and operators from the start of the equation
+    pub expression: String,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "evaluation overflows in equation {}: {}",
+            self.result, self.expression
+        )
+    }
+}
+
+impl Error for Overflow {}
+
+/// Partial evaluation of an expression: operands, and operators waiting for operators of higher
+/// precedence to be applied first.
+#[derive(Debug, Clone)]
+struct Stack {
+    values: Vec<u64>,
+    operators: Vec<Operator>,
+    evaluation: Evaluation,
+}
+
+impl Stack {
+    fn new(first: u64, evaluation: Evaluation) -> Self {
+        Self {
+            values: vec![first],
+            operators: Vec::new(),
+            evaluation,
+        }
+    }
+
+    fn applies_before(&self, previous: &Operator, next: &Operator) -> bool {
+        self.evaluation == Evaluation::LeftToRight || previous.precedence >= next.precedence
+    }
+
+    /// Applies the last operator. Returns None if it overflows.
+    fn reduce(&mut self) -> Option<()> {
+        let operator = self.operators.pop().unwrap();
+        let right = self.values.pop().unwrap();
+        let left = self.values.last_mut().unwrap();
+        *left = (operator.apply)(*left, right)?;
+        Some(())
+    }
+
+    /// Adds an operation. Returns None if it overflows.
+    fn push(&mut self, operator: Operator, value: u64) -> Option<()> {
+        while let Some(previous) = self.operators.last() {
+            if !self.applies_before(previous, &operator) {
+                break;
+            }
+            self.reduce()?;
+        }
+        self.operators.push(operator);
+        self.values.push(value);
+        Some(())
+    }
+
+    /// Value of the whole expression so far, or None if it overflows.
+    fn value(&self) -> Option<u64> {
+        let mut stack = self.clone();
+        while !stack.operators.is_empty() {
+            stack.reduce()?;
+        }
+        Some(stack.values[0])
+    }
+}
+
+/// Value of the numbers combined with the given operators, or None if it overflows.
+pub fn evaluate(nums: &[u64], operators: &[Operator], evaluation: Evaluation) -> Option<u64> {
+    let mut stack = Stack::new(nums[0], evaluation);
+    for (operator, &num) in operators.iter().zip(&nums[1..]) {
+        stack.push(*operator, num)?;
+    }
+    stack.value()
+}
+
+/// Works back from the result, undoing the last operation with each operator. Operators that
+/// cannot give the result prune the search, while ambiguous ones fall back to a forward search of
+/// the numbers before. Keeps the operators used in reverse order, and calls `found` with them in
+/// order until it returns true.
+fn search_back(
+    equation: &Equation,
+    result: u64,
+    nums: &[u64],
+    set: &OperatorSet,
+    used: &mut Vec<Operator>,
+    found: &mut impl FnMut(&[Operator]) -> bool,
+) -> Result<bool, Overflow> {
+    let [rest @ .., last] = nums else {
+        unreachable!("equations have numbers")
+    };
+    if rest.is_empty() {
+        return Ok(result == *last && found(&used.iter().rev().copied().collect::<Vec<_>>()));
+    }
+    for operator in set.operators() {
+        let solved = match (operator.invert)(result, *last) {
+            Inverse::None => false,
+            Inverse::Unique(left) => {
+                used.push(*operator);
+                let solved = search_back(equation, left, rest, set, used, found)?;
+                used.pop();
+                solved
+            }
+            Inverse::Ambiguous => {
+                let stack = Stack::new(rest[0], Evaluation::LeftToRight);
+                let mut finish = |value, before: &[Operator]| {
+                    let operators: Vec<Operator> = (before.iter().chain([operator]))
+                        .chain(used.iter().rev())
+                        .copied()
+                        .collect();
+                    (operator.apply)(value, *last) == Some(result) && found(&operators)
+                };
+                let mut before = Vec::new();
+                search_forward(
+                    equation,
+                    &stack,
                     &rest[1..],
-                    allow_concat,
-                )
-            } else {
-                false
+                    set,
+                    &mut before,
+                    None,
+                    &mut finish,
+                )?
             }
+        };
+        if solved {
+            return Ok(true);
+        }
     }
+    Ok(false)
 }
 
-fn run(input: &str, allow_concat: bool) {
-    let sum: u64 = input
-        .lines()
-        .map(parse_line)
-        .filter_map(|(result, nums)| {
-            if can_solve(result, nums[0], &nums[1..], allow_concat) {
-                Some(result)
-            } else {
-                None
+/// Tries each operator between the expression so far and the next numbers, calling `finish` with
+/// the value and operators of each complete expression until it returns true. As operators never
+/// decrease values when there are no zeros, the search is pruned once the expression is over
+/// `bound`, if any, or overflows. Overflows are errors if they cannot be pruned.
+fn search_forward(
+    equation: &Equation,
+    stack: &Stack,
+    nums: &[u64],
+    set: &OperatorSet,
+    used: &mut Vec<Operator>,
+    bound: Option<u64>,
+    finish: &mut impl FnMut(u64, &[Operator]) -> bool,
+) -> Result<bool, Overflow> {
+    let overflow = |used: &[Operator]| Overflow {
+        result: equation.result,
+        expression: Solution {
+            equation,
+            operators: used.to_vec(),
+        }
+        .expression(),
+    };
+    let prunable = bound.is_some() && !nums.contains(&0);
+    let Some(value) = stack.value() else {
+        return if prunable {
+            Ok(false)
+        } else {
+            Err(overflow(used))
+        };
+    };
+    let [num, rest @ ..] = nums else {
+        return Ok(finish(value, used));
+    };
+    if prunable && bound.is_some_and(|bound| value > bound) {
+        return Ok(false);
+    }
+    for operator in set.operators() {
+        let mut next = stack.clone();
+        used.push(*operator);
+        let solved = match next.push(*operator, *num) {
+            Some(()) => search_forward(equation, &next, rest, set, used, bound, finish)?,
+            // Already over any result
+            None if prunable => false,
+            None => return Err(overflow(used)),
+        };
+        used.pop();
+        if solved {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Calls `found` with the operators of each solution, until it returns true.
+fn search(
+    equation: &Equation,
+    set: &OperatorSet,
+    evaluation: Evaluation,
+    mut found: impl FnMut(&[Operator]) -> bool,
+) -> Result<(), Overflow> {
+    let mut used = Vec::new();
+    match evaluation {
+        // Inverting operators doesn't work with precedence, as the last operation isn't always
+        // the last one applied
+        Evaluation::LeftToRight => {
+            search_back(
+                equation,
+                equation.result,
+                &equation.nums,
+                set,
+                &mut used,
+                &mut found,
+            )?;
+        }
+        Evaluation::Precedence => {
+            let stack = Stack::new(equation.nums[0], evaluation);
+            search_forward(
+                equation,
+                &stack,
+                &equation.nums[1..],
+                set,
+                &mut used,
+                Some(equation.result),
+                &mut |value, used| value == equation.result && found(used),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Operators making the equation true, if any.
+pub fn solve<'a>(
+    equation: &'a Equation,
+    set: &OperatorSet,
+    evaluation: Evaluation,
+) -> Result<Option<Solution<'a>>, Overflow> {
+    let mut solution = None;
+    search(equation, set, evaluation, |operators| {
+        solution = Some(Solution {
+            equation,
+            operators: operators.to_vec(),
+        });
+        true
+    })?;
+    Ok(solution)
+}
+
+/// Number of distinct operator assignments making the equation true.
+pub fn solution_count(
+    equation: &Equation,
+    set: &OperatorSet,
+    evaluation: Evaluation,
+) -> Result<u64, Overflow> {
+    let mut count = 0;
+    search(equation, set, evaluation, |_| {
+        count += 1;
+        false
+    })?;
+    Ok(count)
+}
+
+/// Solutions of the equations that can be solved with the given operators.
+pub fn solutions<'a>(
+    calibration: &'a Calibration,
+    set: &OperatorSet,
+    evaluation: Evaluation,
+) -> Result<Vec<Solution<'a>>, Overflow> {
+    let mut solutions = Vec::new();
+    for equation in &calibration.equations {
+        solutions.extend(solve(equation, set, evaluation)?);
+    }
+    Ok(solutions)
+}
+
+/// Sum of test values of equations solvable with the given operators.
+pub fn total_calibration_result(
+    calibration: &Calibration,
+    set: &OperatorSet,
+    evaluation: Evaluation,
+) -> Result<u64, Overflow> {
+    let solutions = solutions(calibration, set, evaluation)?;
+    Ok(solutions.iter().map(|s| s.equation.result).sum())
+}
+
+/// Sum of test values of equations solvable with `+` and `*`.
+pub fn calibration_result(calibration: &Calibration) -> u64 {
+    total_calibration_result(calibration, &OperatorSet::basic(), Evaluation::LeftToRight).unwrap()
+}
+
+/// Sum of test values of equations solvable with `+`, `*` and concatenation.
+pub fn concat_calibration_result(calibration: &Calibration) -> u64 {
+    total_calibration_result(
+        calibration,
+        &OperatorSet::with_concat(),
+        Evaluation::LeftToRight,
+    )
+    .unwrap()
+}
+
+fn run(input: &str, default: OperatorSet) {
+    let calibration = parse(input).unwrap();
+    let set = OperatorSet::from_params(default);
+    let evaluation = Evaluation::from_params();
+    if flag("count") {
+        let mut total = 0;
+        for equation in &calibration.equations {
+            let count = match solution_count(equation, &set, evaluation) {
+                Ok(count) => count,
+                Err(error) => {
+                    output!("{error}");
+                    return;
+                }
+            };
+            if count > 0 && flag("report") {
+                let plural = if count == 1 { "" } else { "s" };
+                output!("{}: {count} solution{plural}", equation.result);
             }
-        })
-        .sum();
-    println!("{sum}");
+            total += count;
+        }
+        output!("{total}");
+        return;
+    }
+    let solutions = match solutions(&calibration, &set, evaluation) {
+        Ok(solutions) => solutions,
+        Err(error) => {
+            output!("{error}");
+            return;
+        }
+    };
+    if flag("report") {
+        for solution in &solutions {
+            output!("{solution}");
+        }
+    }
+    output!(
+        "{}",
+        solutions.iter().map(|s| s.equation.result).sum::<u64>()
+    );
 }
 
 fn part1(input: &str) {
-    run(input, false);
+    run(input, OperatorSet::basic());
 }
 
 fn part2(input: &str) {
-    run(input, true);
+    run(input, OperatorSet::with_concat());
 }
@@ -1,5 +1,7 @@
-use super::DayInfo;
-use std::collections::{HashMap, HashSet};
+use super::util::gcd;
+use super::{DayInfo, ParseError};
+use crate::api::{flag, param};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::Range;
 
 const EXAMPLE: &str = "\
@@ -16,7 +18,7 @@ const EXAMPLE: &str = "\
 ............
 ............";
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Resonant Collinearity",
     part1,
     part2,
@@ -24,7 +26,15 @@ pub const INFO: DayInfo = DayInfo {
     example2: EXAMPLE,
 };
 
-fn parse(input: &str) -> (Range<i32>, Range<i32>, HashMap<char, Vec<(i32, i32)>>) {
+/// Map bounds and antenna positions, grouped by frequency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AntennaMap {
+    pub x_range: Range<i32>,
+    pub y_range: Range<i32>,
+    pub antennas: HashMap<char, Vec<(i32, i32)>>,
+}
+
+pub fn parse(input: &str) -> Result<AntennaMap, ParseError> {
     let mut x_len = 0;
     let mut y_len = 0;
     let mut map = HashMap::new();
@@ -41,57 +51,163 @@ fn parse(input: &str) -> (Range<i32>, Range<i32>, HashMap<char, Vec<(i32, i32)>>
         }
     }
 
-    (0..x_len, 0..y_len, map)
+    Ok(AntennaMap {
+        x_range: 0..x_len,
+        y_range: 0..y_len,
+        antennas: map,
+    })
 }
 
-fn find_anti_nodes(input: &str, resonant: bool) -> usize {
-    let mut anti_nodes = HashSet::new();
-    let (x_range, y_range, map) = parse(input);
-    for (_, antennas) in map {
-        // Go through every possible combination of antennas
-        for i in 0..antennas.len() - 1 {
-            for j in i + 1..antennas.len() {
-                // Calculate the two anti-nodes
-                let (x1, y1) = antennas[i];
-                let (x2, y2) = antennas[j];
-                if resonant {
-                    anti_nodes.insert((x1, y1));
-                    anti_nodes.insert((x2, y2));
-                }
-                let mut x3 = x1;
-                let mut y3 = y1;
-                let mut x4 = x2;
-                let mut y4 = y2;
-                let dx = x1 - x2;
-                let dy = y1 - y2;
-                loop {
-                    x3 += dx;
-                    y3 += dy;
-                    x4 -= dx;
-                    y4 -= dy;
-                    // Save in set as we need to find unique locations
-                    let has3 = x_range.contains(&x3) && y_range.contains(&y3);
-                    let has4 = x_range.contains(&x4) && y_range.contains(&y4);
-                    if has3 {
-                        anti_nodes.insert((x3, y3));
-                    }
-                    if has4 {
-                        anti_nodes.insert((x4, y4));
-                    }
-                    if !resonant || (!has3 && !has4) {
-                        break;
+/// Which points in line with two antennas of the same frequency are antinodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Harmonics {
+    /// Points outside the pair of antennas, where the farthest antenna is `n / m` times as far as
+    /// the closest one (`n > m`). The puzzle's antinodes are in a 2:1 ratio. Points between the
+    /// antennas are never antinodes, even where the ratio holds (e.g. the 2:1 trisection points).
+    Ratio(u32, u32),
+    /// Every point in line with the antennas, including the antennas themselves
+    Resonant,
+}
+
+impl Harmonics {
+    /// Ratio set by the `ratio` context parameter (`<n>:<m>`), defaulting to the puzzle's 2:1.
+    pub fn ratio_from_params() -> Self {
+        let Some(ratio) = param("ratio") else {
+            return Harmonics::Ratio(2, 1);
+        };
+        let parsed = ratio.split_once(':').and_then(|(n, m)| {
+            let (n, m) = (n.parse().ok()?, m.parse().ok()?);
+            (n > m && m > 0).then_some(Harmonics::Ratio(n, m))
+        });
+        parsed.unwrap_or_else(|| panic!("invalid ratio: {ratio}"))
+    }
+}
+
+/// Antinode locations, for all frequencies and for each one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Antinodes {
+    pub locations: HashSet<(i32, i32)>,
+    pub by_frequency: BTreeMap<char, HashSet<(i32, i32)>>,
+}
+
+impl Antinodes {
+    /// Number of antinode locations of each frequency.
+    pub fn counts(&self) -> BTreeMap<char, usize> {
+        (self.by_frequency.iter())
+            .map(|(&frequency, locations)| (frequency, locations.len()))
+            .collect()
+    }
+
+    /// Map with the antennas, and antinodes marked `#` over them, as in the puzzle.
+    pub fn drawing(&self, map: &AntennaMap) -> String {
+        let mut grid = vec![vec!['.'; map.x_range.len()]; map.y_range.len()];
+        for (&frequency, antennas) in &map.antennas {
+            for &(x, y) in antennas {
+                grid[y as usize][x as usize] = frequency;
+            }
+        }
+        for &(x, y) in &self.locations {
+            grid[y as usize][x as usize] = '#';
+        }
+        (grid.iter())
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect()
+    }
+}
+
+/// Antinodes of a pair of antennas, on the lattice points of the line through them.
+fn pair_antinodes(
+    map: &AntennaMap,
+    (x1, y1): (i32, i32),
+    (x2, y2): (i32, i32),
+    harmonics: Harmonics,
+    mut found: impl FnMut((i32, i32)),
+) {
+    let AntennaMap {
+        x_range, y_range, ..
+    } = map;
+    let (dx, dy) = (x2 as i64 - x1 as i64, y2 as i64 - y1 as i64);
+    // Smallest step between lattice points on the line, the antennas are `distance` steps apart
+    let distance = gcd(dx, dy);
+    let (step_x, step_y) = (dx / distance, dy / distance);
+    // None when the point overflows, which puts it far off the map anyway
+    let at = |steps: i64| {
+        let x = step_x.checked_mul(steps)?.checked_add(x1 as i64)?;
+        let y = step_y.checked_mul(steps)?.checked_add(y1 as i64)?;
+        Some((i32::try_from(x).ok()?, i32::try_from(y).ok()?))
+    };
+    let on_map = |&(x, y): &(i32, i32)| x_range.contains(&x) && y_range.contains(&y);
+    match harmonics {
+        Harmonics::Ratio(n, m) => {
+            // Going beyond the second antenna by `m / (n - m)` times the distance between them
+            let (n, m) = (n as i64, m as i64);
+            let beyond = distance * m;
+            if beyond % (n - m) == 0 {
+                let beyond = beyond / (n - m);
+                let points = [distance.checked_add(beyond), beyond.checked_neg()];
+                for point in points.into_iter().flatten().filter_map(at) {
+                    if on_map(&point) {
+                        found(point);
                     }
                 }
             }
         }
+        Harmonics::Resonant => {
+            for direction in [1, -1] {
+                (0..)
+                    .map_while(|steps| at(steps * direction).filter(on_map))
+                    .for_each(&mut found);
+            }
+        }
+    }
+}
+
+/// Antinodes of every pair of antennas with the same frequency.
+pub fn antinodes(map: &AntennaMap, harmonics: Harmonics) -> Antinodes {
+    let mut by_frequency = BTreeMap::new();
+    for (&frequency, antennas) in &map.antennas {
+        let mut locations = HashSet::new();
+        for (i, &first) in antennas.iter().enumerate() {
+            for &second in &antennas[i + 1..] {
+                pair_antinodes(map, first, second, harmonics, |point| {
+                    locations.insert(point);
+                });
+            }
+        }
+        by_frequency.insert(frequency, locations);
+    }
+    Antinodes {
+        locations: by_frequency.values().flatten().copied().collect(),
+        by_frequency,
+    }
+}
+
+/// Number of unique anti-node locations.
+pub fn antinode_count(map: &AntennaMap) -> usize {
+    antinodes(map, Harmonics::Ratio(2, 1)).locations.len()
+}
+
+/// Number of unique anti-node locations, taking resonant harmonics into account.
+pub fn resonant_antinode_count(map: &AntennaMap) -> usize {
+    antinodes(map, Harmonics::Resonant).locations.len()
+}
+
+fn run(input: &str, harmonics: Harmonics) {
+    let map = parse(input).unwrap();
+    let antinodes = antinodes(&map, harmonics);
+    if flag("report") {
+        output!("{}", antinodes.drawing(&map));
+        for (frequency, count) in antinodes.counts() {
+            output!("{frequency}: {count}");
+        }
     }
-    anti_nodes.len()
+    output!("{}", antinodes.locations.len());
 }
 
 fn part1(input: &str) {
-    println!("{}", find_anti_nodes(input, false));
+    run(input, Harmonics::ratio_from_params());
 }
 
 fn part2(input: &str) {
-    println!("{}", find_anti_nodes(input, true));
+    run(input, Harmonics::Resonant);
 }
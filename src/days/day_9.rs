@@ -1,7 +1,10 @@
-use super::DayInfo;
-use std::iter::repeat;
+use super::{DayInfo, ParseError};
+use crate::api::{flag, param};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashSet};
+use std::fmt::{Display, Formatter};
 
-pub const INFO: DayInfo = DayInfo {
+pub(crate) const INFO: DayInfo = DayInfo {
     name: "Disk Fragmenter",
     part1,
     part2,
@@ -9,120 +12,416 @@ pub const INFO: DayInfo = DayInfo {
     example2: "2333133121414131402",
 };
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Block {
-    File(u32),
-    Free,
+/// Contiguous sectors of the disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: u64,
+    pub length: u32,
 }
 
-fn part1(input: &str) {
-    let mut disk = parse(input);
-    let mut left = 0;
-    let mut right = disk.len() - 1;
-    'outer: loop {
-        while disk[left] != Block::Free {
-            left += 1;
-            if left >= right {
-                break 'outer;
+impl Span {
+    pub fn end(&self) -> u64 {
+        self.start + self.length as u64
+    }
+}
+
+/// Sectors holding (a part of) a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileRun {
+    pub id: u32,
+    pub span: Span,
+}
+
+/// Disk map as runs of file sectors and free spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    /// Files, by increasing id and position
+    pub files: Vec<FileRun>,
+    /// Free spans between the files, by position
+    pub free: Vec<Span>,
+}
+
+impl Disk {
+    /// Number of sectors.
+    pub fn size(&self) -> u64 {
+        let files = self.files.last().map_or(0, |file| file.span.end());
+        files.max(self.free.last().map_or(0, Span::end))
+    }
+}
+
+/// Longest span of a single digit in a disk map
+const MAX_SPAN: usize = 9;
+/// Largest disk drawn in reports
+const DRAWING_LIMIT: u64 = 200;
+
+/// Moves blocks one at a time from the end of the disk to the left-most free sector. Returns the
+/// resulting file runs, where moved files can be split into several runs.
+pub fn compact_blocks(disk: &Disk) -> Vec<FileRun> {
+    let mut files = disk.files.clone();
+    let mut moved = Vec::new();
+    for span in &disk.free {
+        let Span { mut start, length } = *span;
+        let mut room = length;
+        while room > 0 {
+            // Blocks are taken from the end of the right-most file, which shrinks
+            let Some(file) = files.last_mut() else { break };
+            if file.span.length == 0 {
+                files.pop();
+                continue;
             }
-        }
-        while disk[right] == Block::Free {
-            right -= 1;
-            if left >= right {
-                break 'outer;
+            if file.span.start <= start {
+                break;
             }
+            let taken = room.min(file.span.length);
+            file.span.length -= taken;
+            moved.push(FileRun {
+                id: file.id,
+                span: Span {
+                    start,
+                    length: taken,
+                },
+            });
+            start += taken as u64;
+            room -= taken;
         }
-        disk.swap(left, right);
     }
-    println!("{}", checksum(&disk));
+    files.extend(moved);
+    files.retain(|file| file.span.length > 0);
+    files
 }
 
-fn part2(input: &str) {
-    let mut disk = parse(input);
-    // left-most empty space
-    let mut left = 0;
-    while disk[left] != Block::Free {
-        left += 1;
-    }
-    // right-most unmoved block
-    let mut right = disk.len() - 1;
-    // loop over files
-    while left < right {
-        // Find the next file to be moved
-        let mut right_start = right;
-        while disk[right_start - 1] == disk[right] {
-            right_start -= 1;
-        }
-        // Find an empty spot of the same size
-        let mut left_start = left;
-        let mut left_end = left;
-        let can_move = loop {
-            if left_end - left_start == right - right_start {
-                break true;
-            }
-            left_end += 1;
-            if disk[left_end] != Block::Free {
-                if left_end >= right_start {
-                    break false;
-                }
-                while disk[left_end] != Block::Free {
-                    left_end += 1;
-                }
-                if left_end >= right_start {
-                    break false;
-                }
-                left_start = left_end;
-            }
+/// Free spans by start position, in heaps by length. Spans longer than a digit (after empty files)
+/// share the heap of the longest ones.
+type FreeSpans = [BinaryHeap<Reverse<(u64, u32)>>; MAX_SPAN + 1];
+
+fn add_free(free: &mut FreeSpans, span: Span) {
+    free[(span.length as usize).min(MAX_SPAN)].push(Reverse((span.start, span.length)));
+}
+
+/// Moves whole files, from the highest id, to the left-most free span that fits them. Returns the
+/// resulting file runs.
+///
+/// Free spans are kept in one min-heap of start positions per span length, so the left-most span
+/// fitting a file is the smallest of the heap tops for its length and above.
+pub fn compact_files(disk: &Disk) -> Vec<FileRun> {
+    let mut free = FreeSpans::default();
+    for &span in &disk.free {
+        add_free(&mut free, span);
+    }
+    let mut files = disk.files.clone();
+    for file in files.iter_mut().rev() {
+        let length = file.span.length as usize;
+        if length == 0 {
+            continue;
+        }
+        let fitting = (length.min(MAX_SPAN)..=MAX_SPAN)
+            .filter_map(|bucket| Some((free[bucket].peek()?.0, bucket)))
+            .min();
+        let Some(((start, span_length), bucket)) = fitting else {
+            continue;
         };
-        // Move the file
-        if can_move {
-            let (s1, s2) = disk.split_at_mut(right_start);
-            s1[left_start..=left_end].swap_with_slice(&mut s2[0..=right - right_start]);
-            // Find the next empty space
-            if left == left_start {
-                left = left_end + 1;
-            }
-            while disk[left] != Block::Free {
-                left += 1;
+        if start >= file.span.start {
+            continue;
+        }
+        // Files are moved left and by decreasing position, the space they leave is never used
+        free[bucket].pop();
+        file.span.start = start;
+        if span_length > length as u32 {
+            let rest = Span {
+                start: start + length as u64,
+                length: span_length - length as u32,
+            };
+            add_free(&mut free, rest);
+        }
+    }
+    files
+}
+
+/// Way of choosing where files are moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Blocks one at a time to the left-most free sector, as in part 1
+    Blocks,
+    /// Whole files to the left-most span fitting them, as in part 2
+    FirstFit,
+    /// Whole files to the smallest span fitting them
+    BestFit,
+    /// Whole files to the largest span
+    WorstFit,
+}
+
+impl Strategy {
+    /// Strategy set by the `strategy` context parameter (`blocks`, `first_fit`, `best_fit` or
+    /// `worst_fit`), or the given default.
+    pub fn from_params(default: Self) -> Self {
+        match param("strategy").as_deref() {
+            None => default,
+            Some("blocks") => Strategy::Blocks,
+            Some("first_fit") => Strategy::FirstFit,
+            Some("best_fit") => Strategy::BestFit,
+            Some("worst_fit") => Strategy::WorstFit,
+            Some(strategy) => panic!("invalid strategy: {strategy}"),
+        }
+    }
+}
+
+/// Free spans indexed by position and by length, merging adjacent spans.
+#[derive(Debug, Default)]
+struct FreeList {
+    by_start: BTreeMap<u64, u32>,
+    by_length: BTreeMap<u32, BTreeSet<u64>>,
+}
+
+impl FreeList {
+    fn insert(&mut self, Span { start, length }: Span) {
+        self.by_start.insert(start, length);
+        self.by_length.entry(length).or_default().insert(start);
+    }
+
+    fn remove(&mut self, Span { start, length }: Span) {
+        self.by_start.remove(&start);
+        let starts = self.by_length.get_mut(&length).unwrap();
+        starts.remove(&start);
+        if starts.is_empty() {
+            self.by_length.remove(&length);
+        }
+    }
+
+    /// Frees a span, merging it with the free spans around it.
+    fn free(&mut self, mut span: Span) {
+        let before = self.by_start.range(..span.start).next_back();
+        if let Some((&start, &length)) = before {
+            let before = Span { start, length };
+            if before.end() == span.start {
+                self.remove(before);
+                span = Span {
+                    start,
+                    length: length + span.length,
+                };
             }
         }
-        // Find the next file
-        right = right_start - 1;
-        while disk[right] == Block::Free {
-            right -= 1;
-        }
-    }
-    println!("{}", checksum(&disk));
-}
-
-fn parse(input: &str) -> Vec<Block> {
-    input
-        .lines()
-        .next()
-        .unwrap()
-        .chars()
-        .map(|c| c.to_digit(10).unwrap())
-        .enumerate()
-        .flat_map(|(index, len)| {
-            repeat(if index % 2 != 0 {
-                Block::Free
-            } else {
-                Block::File(index as u32 / 2)
-            })
-            .take(len as usize)
-        })
-        .collect()
+        if let Some(&length) = self.by_start.get(&span.end()) {
+            self.remove(Span {
+                start: span.end(),
+                length,
+            });
+            span.length += length;
+        }
+        self.insert(span);
+    }
+
+    /// Span chosen for a file by the strategy, among the ones before the given position.
+    fn choose(&self, strategy: Strategy, length: u32, before: u64) -> Option<Span> {
+        let mut fitting = (self.by_length.range(length..)).filter_map(|(&length, starts)| {
+            let start = *starts.first().filter(|&&start| start < before)?;
+            Some(Span { start, length })
+        });
+        match strategy {
+            Strategy::Blocks => unreachable!("blocks are not allocated as files"),
+            Strategy::FirstFit => fitting.min_by_key(|span| span.start),
+            Strategy::BestFit => fitting.next(),
+            Strategy::WorstFit => fitting.next_back(),
+        }
+    }
 }
 
-fn checksum(disk: &[Block]) -> u64 {
-    disk.iter()
-        .enumerate()
-        .map(|(index, &file)| {
-            if let Block::File(id) = file {
-                index as u64 * id as u64
-            } else {
-                0
+/// Moves whole files, from the highest id, to the span chosen by the strategy. Files are only
+/// moved left unless `rightward` is set, in which case the space they leave can be used too.
+/// Returns the resulting file runs.
+pub fn compact_files_with(disk: &Disk, strategy: Strategy, rightward: bool) -> Vec<FileRun> {
+    let mut free = FreeList::default();
+    for &span in &disk.free {
+        free.insert(span);
+    }
+    let mut files = disk.files.clone();
+    for file in files.iter_mut().rev() {
+        if file.span.length == 0 {
+            continue;
+        }
+        let limit = if rightward { u64::MAX } else { file.span.start };
+        let Some(span) = free.choose(strategy, file.span.length, limit) else {
+            continue;
+        };
+        free.remove(span);
+        if span.length > file.span.length {
+            free.insert(Span {
+                start: span.start + file.span.length as u64,
+                length: span.length - file.span.length,
+            });
+        }
+        free.free(file.span);
+        file.span.start = span.start;
+    }
+    files
+}
+
+/// Disk after compaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compaction {
+    /// File runs by position
+    pub files: Vec<FileRun>,
+    pub size: u64,
+    pub moved_files: usize,
+}
+
+/// Compacts the disk with the given strategy. Whole files are only moved rightwards if
+/// `rightward` is set.
+pub fn compact(disk: &Disk, strategy: Strategy, rightward: bool) -> Compaction {
+    let mut files = match strategy {
+        Strategy::Blocks => compact_blocks(disk),
+        Strategy::FirstFit if !rightward => compact_files(disk),
+        _ => compact_files_with(disk, strategy, rightward),
+    };
+    files.retain(|file| file.span.length > 0);
+    files.sort_by_key(|file| file.span.start);
+    let moved: HashSet<u32> = (files.iter())
+        .filter(|file| file.span != disk.files[file.id as usize].span)
+        .map(|file| file.id)
+        .collect();
+    Compaction {
+        files,
+        size: disk.size(),
+        moved_files: moved.len(),
+    }
+}
+
+impl Compaction {
+    pub fn checksum(&self) -> u128 {
+        checksum(&self.files)
+    }
+
+    /// Free spans between the files and up to the end of the disk.
+    pub fn free_spans(&self) -> Vec<Span> {
+        let mut spans = Vec::new();
+        let mut start = 0;
+        let ends = (self.files.iter().map(|file| file.span)).chain([Span {
+            start: self.size,
+            length: 0,
+        }]);
+        for span in ends {
+            if span.start > start {
+                spans.push(Span {
+                    start,
+                    length: (span.start - start) as u32,
+                });
             }
+            start = start.max(span.end());
+        }
+        spans
+    }
+
+    pub fn largest_free_span(&self) -> u32 {
+        self.free_spans()
+            .iter()
+            .map(|span| span.length)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// External fragmentation: 0 when all free sectors are contiguous, approaching 1 as they are
+    /// split into small spans.
+    pub fn fragmentation(&self) -> f64 {
+        let free: u64 = self
+            .free_spans()
+            .iter()
+            .map(|span| span.length as u64)
+            .sum();
+        if free == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_free_span() as f64 / free as f64
+        }
+    }
+
+    /// Drawing of the disk as in the puzzle, such as `0099811188827773336446555566..............`.
+    /// Files ids are shown by their last digit.
+    pub fn drawing(&self) -> String {
+        let mut drawing = vec![b'.'; self.size as usize];
+        for file in &self.files {
+            let Span { start, length } = file.span;
+            let digit = b'0' + (file.id % 10) as u8;
+            drawing[start as usize..][..length as usize].fill(digit);
+        }
+        String::from_utf8(drawing).unwrap()
+    }
+}
+
+impl Display for Compaction {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if self.size <= DRAWING_LIMIT {
+            writeln!(f, "{}", self.drawing())?;
+        }
+        writeln!(f, "Checksum: {}", self.checksum())?;
+        writeln!(f, "Moved files: {}", self.moved_files)?;
+        writeln!(f, "Largest free span: {}", self.largest_free_span())?;
+        write!(f, "Fragmentation: {:.3}", self.fragmentation())
+    }
+}
+
+/// Sum of the positions of each block multiplied by its file id. It doesn't always fit in 64 bits
+/// for disk maps with millions of digits.
+pub fn checksum(files: &[FileRun]) -> u128 {
+    (files.iter())
+        .map(|file| {
+            // Sum of start..end
+            let Span { start, length } = file.span;
+            let (start, length) = (start as u128, length as u128);
+            let positions = start * length + length * length.saturating_sub(1) / 2;
+            file.id as u128 * positions
         })
         .sum()
 }
+
+/// Checksum after moving blocks one at a time to the left-most free space.
+pub fn compact_blocks_checksum(disk: &Disk) -> u128 {
+    checksum(&compact_blocks(disk))
+}
+
+/// Checksum after moving whole files to the left-most free span that fits them.
+pub fn compact_files_checksum(disk: &Disk) -> u128 {
+    checksum(&compact_files(disk))
+}
+
+pub fn parse(input: &str) -> Result<Disk, ParseError> {
+    let line = input.lines().next().unwrap_or_default();
+    let mut files = Vec::new();
+    let mut free: Vec<Span> = Vec::new();
+    let mut start = 0;
+    for (index, c) in line.chars().enumerate() {
+        let Some(length) = c.to_digit(10) else {
+            return Err(ParseError::new(format!("invalid digit: {c:?}")));
+        };
+        let span = Span { start, length };
+        if index % 2 == 0 {
+            files.push(FileRun {
+                id: index as u32 / 2,
+                span,
+            });
+        } else if length > 0 {
+            // Free spans around an empty file are a single span
+            match free.last_mut() {
+                Some(last) if last.end() == start => last.length += length,
+                _ => free.push(span),
+            }
+        }
+        start = span.end();
+    }
+    Ok(Disk { files, free })
+}
+
+fn run(input: &str, default: Strategy) {
+    let disk = parse(input).unwrap();
+    let compaction = compact(&disk, Strategy::from_params(default), flag("rightward"));
+    if flag("report") {
+        output!("{compaction}");
+    }
+    output!("{}", compaction.checksum());
+}
+
+fn part1(input: &str) {
+    run(input, Strategy::Blocks);
+}
+
+fn part2(input: &str) {
+    run(input, Strategy::FirstFit);
+}
@@ -1,3 +1,7 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 mod util;
 
 pub struct DayInfo {
@@ -8,6 +12,48 @@ pub struct DayInfo {
     pub example2: &'static str,
 }
 
+/// Error returned when a puzzle input is malformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line of the input the error was found on, if known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    /// Sets the line of the error, from its 0-based index (as given by `lines().enumerate()`).
+    /// Keeps the existing line, if any.
+    pub fn at_line(mut self, index: usize) -> Self {
+        self.line.get_or_insert(index + 1);
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a number, or any other [FromStr] value, reporting the invalid text on error.
+fn number<T: FromStr>(s: &str) -> Result<T, ParseError> {
+    s.trim()
+        .parse()
+        .map_err(|_| ParseError::new(format!("invalid number: {s:?}")))
+}
+
 macro_rules! days {
     ($num:literal) => {
         use seq_macro::seq;
@@ -21,7 +67,7 @@ macro_rules! days {
         });
 
         seq!(N in 1..=$num {
-            mod day_~N;
+            pub mod day_~N;
         });
     };
 }
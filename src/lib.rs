@@ -1,7 +1,20 @@
+/// Prints a line of solution output, or captures it when the solution runs through the server.
+macro_rules! output {
+    () => {
+        $crate::api::output(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::api::output(format_args!($($arg)*))
+    };
+}
+
 mod days;
 
 use days::DayInfo;
 use days::DAYS;
+use seq_macro::seq;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
@@ -9,6 +22,17 @@ use std::sync::RwLock;
 use std::time::Instant;
 
 pub mod api;
+pub mod memo;
+pub mod player;
+pub mod render;
+pub mod server;
+
+pub use days::ParseError;
+
+// Typed solutions for each day, as `day1` to `day25`
+seq!(N in 1..=25 {
+    pub use days::day_~N as day~N;
+});
 
 pub enum Part {
     One = 1,
@@ -17,6 +41,11 @@ pub enum Part {
 }
 
 static IS_EXAMPLE: RwLock<bool> = RwLock::new(false);
+static PARAMS: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+thread_local! {
+    static CAPTURE: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
 
 /// Runs the solution for the given 1-indexed day number (1-25).
 /// * `day` - day number (1-25)
@@ -2,17 +2,50 @@ use aoc_2024_rust::*;
 use std::io;
 use std::io::BufRead;
 
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8024";
+
 fn main() {
     println!("Advent of Code 2023: Rust");
     println!("Enter which day/part you would like to run, and in which mode (full/examples).");
     println!("- full mode - run using input/output files");
     println!("- examples mode - run using example input, outputting to stdout");
     println!();
-    println!("Format: [<day>] [<mode>]");
+    println!("Format: [<day>] [<mode>] [<name>=<value>...], or: serve [<address>]");
     println!("  day - day number (1-25), or \"all\" (default)");
     println!("  mode - one of: f, f1, f2, e, e1, e2 (defaults to f, only f/e allowed if day is \"all\"):");
     println!("    - f1/f2/f - run part(s) 1/2/both in full mode");
     println!("    - e1/e2/e - run part(s) 1/2/both in examples mode");
+    println!("  name=value - context parameters for the solutions, for example:");
+    println!("    - render=<dir> - save visualisations to the given directory");
+    println!("    - render_scale=<n> - size in pixels of a rendered tile (default 4)");
+    println!("    - highlight=<n> - update to highlight in the rendered rule graph (day 5)");
+    println!("    - play=1 - step through simulations in the terminal (days 6, 15)");
+    println!("    - play_delay=<ms> - initial delay between steps when playing (default 100)");
+    println!("    - report=1 - print a detailed report of how the answer was found (days 1-9, 19)");
+    println!("    - stream=1 - read pairs from stdin, printing running totals after each blank line (day 1, e1/e2)");
+    println!(
+        "    - tolerance=<k> - number of levels the problem dampener may remove (day 2, default 1)"
+    );
+    println!("    - min_step=<n>, max_step=<n>, allow_equal=1, direction=<increasing|decreasing|either> - safety rules (day 2)");
+    println!("    - explain=1 - print why each report is unsafe (day 2)");
+    println!("    - words=<w>,..., directions=<n|ne|e|...>,..., wrap=1 - word search options (day 4, part 1)");
+    println!("    - stencil=<rows separated by />, symmetry=<none|rotations|mirrors|all> - shape to find (day 4, part 2)");
+    println!("    - candidate=<x>,<y> - draw the loop caused by an obstacle at this position (day 6, part 2)");
+    println!("    - turn=<left|right> - way the guards turn when facing a wall (day 6)");
+    println!("    - visited=<shared|separate> - count tiles visited by several guards once or for each guard (day 6)");
+    println!(
+        "    - operators=<op>,... - operators among +, * and || to combine numbers with (day 7)"
+    );
+    println!("    - evaluation=<left_to_right|precedence>, concat_precedence=<n> - order of operations, + is 1, * is 2 and || is 3 by default (day 7)");
+    println!(
+        "    - count=1 - count every operator assignment solving the equations instead (day 7)"
+    );
+    println!("    - ratio=<n>:<m> - distance ratio of antinodes to their antennas (day 8, part 1, default 2:1)");
+    println!("    - strategy=<blocks|first_fit|best_fit|worst_fit>, rightward=1 - how files are moved, and whether they can move right (day 9)");
+    println!("    - start_height=<n>, end_height=<n> - heights trails go between (day 10, default 0 and 9)");
+    println!("    - blinks=<n> - number of blinks (day 11, default 25 and 75)");
+    println!("    - big=1 - count stones with big integers, for counts over 64 bits (day 11)");
+    println!("  serve - run as an HTTP server instead (default address: {DEFAULT_ADDRESS})");
 
     let line = io::stdin().lock().lines().next().unwrap().unwrap();
     if line.trim().is_empty() {
@@ -20,7 +53,19 @@ fn main() {
         return;
     }
 
-    let mut words = line.split_whitespace();
+    if let Some(address) = line.trim().strip_prefix("serve") {
+        let address = Some(address.trim()).filter(|a| !a.is_empty());
+        server::serve(address.unwrap_or(DEFAULT_ADDRESS)).unwrap();
+        return;
+    }
+
+    let (params, words): (Vec<&str>, Vec<&str>) =
+        line.split_whitespace().partition(|word| word.contains('='));
+    api::set_params(params.into_iter().map(|param| {
+        let (name, value) = param.split_once('=').unwrap();
+        (name.to_owned(), value.to_owned())
+    }));
+    let mut words = words.into_iter();
 
     let day = match words.next() {
         None | Some("all") => {
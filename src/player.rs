@@ -0,0 +1,152 @@
+//! Terminal player for stepwise simulations.
+//!
+//! Any iterator of displayable states can be played: each state is redrawn in place using ANSI
+//! escapes. The player is controlled by typing commands on stdin, followed by Enter.
+
+use crate::api::{flag, param};
+use std::fmt::Display;
+use std::io::{stdin, stdout, BufRead, Write};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+const HELP: &str =
+    "[Enter] play/pause  [n] next  [b] back  [+/-] speed  [g <step>] go to step  [q] quit";
+const MIN_DELAY: Duration = Duration::from_millis(1);
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Toggle,
+    Next,
+    Back,
+    Faster,
+    Slower,
+    Goto(usize),
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        Some(match words.next() {
+            None | Some("p") => Command::Toggle,
+            Some("n") => Command::Next,
+            Some("b") => Command::Back,
+            Some("+") => Command::Faster,
+            Some("-") => Command::Slower,
+            Some("g") => Command::Goto(words.next()?.parse().ok()?),
+            Some("q") => Command::Quit,
+            _ => return None,
+        })
+    }
+}
+
+/// Lines typed on stdin. A single reader thread is shared by all players, so that a finished
+/// player doesn't swallow the input meant for the next one.
+fn input() -> &'static Mutex<Receiver<String>> {
+    static INPUT: OnceLock<Mutex<Receiver<String>>> = OnceLock::new();
+    INPUT.get_or_init(|| {
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            for line in stdin().lock().lines() {
+                if sender.send(line.unwrap()).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(receiver)
+    })
+}
+
+/// Whether playing was requested with the `play` parameter.
+pub fn enabled() -> bool {
+    flag("play")
+}
+
+/// Plays the states of a simulation, starting paused on the first one. The initial delay between
+/// steps (in milliseconds) can be set using the `play_delay` parameter.
+pub fn play<S: Display, I: Iterator<Item = S>>(steps: I) {
+    let mut steps = steps.fuse();
+    let mut history: Vec<String> = Vec::new();
+    let mut current = 0;
+    let mut playing = false;
+    let mut delay = Duration::from_millis(param("play_delay").map_or(100, |s| s.parse().unwrap()));
+    let input = input().lock().unwrap();
+
+    // Makes sure the state at the given step is rendered, returns false if the simulation ended
+    let mut advance_to = |history: &mut Vec<String>, step: usize| {
+        while history.len() <= step {
+            match steps.next() {
+                Some(state) => history.push(state.to_string()),
+                None => return false,
+            }
+        }
+        true
+    };
+
+    if !advance_to(&mut history, 0) {
+        return;
+    }
+    loop {
+        let finished = !advance_to(&mut history, current + 1);
+        if finished {
+            playing = false;
+        }
+        let mut out = stdout().lock();
+        write!(out, "\x1b[H\x1b[2J{}", history[current]).unwrap();
+        writeln!(
+            out,
+            "\nstep {current}{} - {} - {delay:?}/step\n{HELP}",
+            if finished { " (end)" } else { "" },
+            if playing { "playing" } else { "paused" },
+        )
+        .unwrap();
+        out.flush().unwrap();
+        drop(out);
+
+        let command = if playing {
+            match input.recv_timeout(delay) {
+                Ok(line) => Command::parse(&line),
+                Err(RecvTimeoutError::Timeout) => {
+                    current += 1;
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    // No more input, just keep playing
+                    thread::sleep(delay);
+                    current += 1;
+                    continue;
+                }
+            }
+        } else {
+            match input.recv() {
+                Ok(line) => Command::parse(&line),
+                Err(_) => Some(Command::Quit),
+            }
+        };
+
+        match command {
+            None => (),
+            Some(Command::Toggle) => playing = !playing,
+            Some(Command::Next) => {
+                playing = false;
+                if !finished {
+                    current += 1;
+                }
+            }
+            Some(Command::Back) => {
+                playing = false;
+                current = current.saturating_sub(1);
+            }
+            Some(Command::Faster) => delay = (delay / 2).max(MIN_DELAY),
+            Some(Command::Slower) => delay = (delay * 2).min(MAX_DELAY),
+            Some(Command::Goto(step)) => {
+                advance_to(&mut history, step);
+                current = step.min(history.len() - 1);
+            }
+            Some(Command::Quit) => break,
+        }
+    }
+}
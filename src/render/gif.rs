@@ -0,0 +1,185 @@
+use super::{Frame, Palette};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const MAX_CODE: u16 = 4095;
+
+/// Writes variable-width codes, least significant bit first, as GIF data sub-blocks.
+struct CodeWriter<'a, W: Write> {
+    out: &'a mut W,
+    block: Vec<u8>,
+    bits: u32,
+    bit_count: u32,
+}
+
+impl<'a, W: Write> CodeWriter<'a, W> {
+    fn new(out: &'a mut W) -> Self {
+        Self {
+            out,
+            block: Vec::with_capacity(255),
+            bits: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn write(&mut self, code: u16, width: u32) -> std::io::Result<()> {
+        self.bits |= (code as u32) << self.bit_count;
+        self.bit_count += width;
+        while self.bit_count >= 8 {
+            self.push_byte(self.bits as u8)?;
+            self.bits >>= 8;
+            self.bit_count -= 8;
+        }
+        Ok(())
+    }
+
+    fn push_byte(&mut self, byte: u8) -> std::io::Result<()> {
+        self.block.push(byte);
+        if self.block.len() == 255 {
+            self.flush_block()?;
+        }
+        Ok(())
+    }
+
+    fn flush_block(&mut self) -> std::io::Result<()> {
+        if !self.block.is_empty() {
+            self.out.write_all(&[self.block.len() as u8])?;
+            self.out.write_all(&self.block)?;
+            self.block.clear();
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> std::io::Result<()> {
+        if self.bit_count > 0 {
+            self.push_byte(self.bits as u8)?;
+        }
+        self.flush_block()?;
+        // Block terminator
+        self.out.write_all(&[0])
+    }
+}
+
+fn lzw_encode<W: Write>(out: &mut W, pixels: &[u8], min_code_size: u32) -> std::io::Result<()> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut writer = CodeWriter::new(out);
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut width = min_code_size + 1;
+
+    writer.write(clear, width)?;
+    let mut pixels = pixels.iter().copied();
+    if let Some(first) = pixels.next() {
+        let mut prefix = first as u16;
+        for pixel in pixels {
+            if let Some(&code) = dict.get(&(prefix, pixel)) {
+                prefix = code;
+                continue;
+            }
+            writer.write(prefix, width)?;
+            if next > MAX_CODE {
+                // Table is full, start over
+                writer.write(clear, width)?;
+                dict.clear();
+                next = end + 1;
+                width = min_code_size + 1;
+            } else {
+                dict.insert((prefix, pixel), next);
+                if next == 1 << width && width < 12 {
+                    width += 1;
+                }
+                next += 1;
+            }
+            prefix = pixel as u16;
+        }
+        writer.write(prefix, width)?;
+    }
+    writer.write(end, width)?;
+    writer.finish()
+}
+
+/// Sequence of frames, encoded as a looping animated GIF.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    palette: Palette,
+    frames: Vec<Frame>,
+    delay: u16,
+}
+
+impl Animation {
+    /// Creates an empty animation, showing each frame for `delay` hundredths of a second.
+    pub fn new(palette: Palette, delay: u16) -> Self {
+        Self {
+            palette,
+            frames: Vec::new(),
+            delay,
+        }
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Appends a frame. All frames must have the same size.
+    pub fn push(&mut self, frame: Frame) {
+        if let Some(first) = self.frames.first() {
+            assert!(
+                first.width == frame.width && first.height == frame.height,
+                "frame size mismatch"
+            );
+        }
+        self.frames.push(frame);
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn write_gif<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        let (width, height) = self
+            .frames
+            .first()
+            .map_or((0, 0), |frame| (frame.width, frame.height));
+        // Color table has 2^(size + 1) entries
+        let colors = self.palette.colors();
+        let size = (colors.len().max(2).next_power_of_two().ilog2() - 1) as u8;
+
+        out.write_all(b"GIF89a")?;
+        out.write_all(&(width as u16).to_le_bytes())?;
+        out.write_all(&(height as u16).to_le_bytes())?;
+        out.write_all(&[0x80 | (size << 4) | size, 0, 0])?;
+        out.write_all(colors.as_flattened())?;
+        for _ in colors.len()..2 << size {
+            out.write_all(&[0; 3])?;
+        }
+        // Loop forever
+        out.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+        for frame in &self.frames {
+            out.write_all(&[0x21, 0xf9, 4, 0])?;
+            out.write_all(&self.delay.to_le_bytes())?;
+            out.write_all(&[0, 0])?;
+            out.write_all(&[0x2c, 0, 0, 0, 0])?;
+            out.write_all(&(frame.width as u16).to_le_bytes())?;
+            out.write_all(&(frame.height as u16).to_le_bytes())?;
+            out.write_all(&[0])?;
+            let min_code_size = (size as u32 + 1).max(2);
+            out.write_all(&[min_code_size as u8])?;
+            lzw_encode(out, &frame.pixels, min_code_size)?;
+        }
+        out.write_all(&[0x3b])
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_gif(&mut out)?;
+        out.flush()
+    }
+}
@@ -0,0 +1,174 @@
+//! Rendering of grid states into images, for debugging and sharing visualisations.
+//!
+//! Grids are rows of bytes (usually the characters of the puzzle map), which are turned into
+//! colors using a [Palette]. Everything is encoded by hand, so no system libraries are needed.
+
+mod gif;
+mod png;
+
+use crate::api::param;
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub use gif::Animation;
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+pub const GRAY: Rgb = [128, 128, 128];
+pub const RED: Rgb = [220, 50, 47];
+pub const GREEN: Rgb = [0, 200, 83];
+pub const BLUE: Rgb = [38, 139, 210];
+pub const YELLOW: Rgb = [255, 204, 0];
+
+/// Maps grid cells (bytes) to colors. Cells without an explicit color use the background.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    colors: Vec<Rgb>,
+    indices: [u8; 256],
+}
+
+impl Palette {
+    pub fn new(background: Rgb) -> Self {
+        Self {
+            colors: vec![background],
+            indices: [0; 256],
+        }
+    }
+
+    /// Assigns a color to a cell. A palette can hold at most 256 distinct colors.
+    pub fn with(mut self, cell: u8, color: Rgb) -> Self {
+        let index = match self.colors.iter().position(|&c| c == color) {
+            Some(index) => index,
+            None => {
+                assert!(self.colors.len() < 256, "too many colors in palette");
+                self.colors.push(color);
+                self.colors.len() - 1
+            }
+        };
+        self.indices[cell as usize] = index as u8;
+        self
+    }
+
+    pub fn index(&self, cell: u8) -> u8 {
+        self.indices[cell as usize]
+    }
+
+    pub fn colors(&self) -> &[Rgb] {
+        &self.colors
+    }
+}
+
+/// Single image, stored as palette indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Renders a grid, drawing each cell as a `scale`x`scale` square. Short rows are padded
+    /// with the background color.
+    pub fn from_grid<R: AsRef<[u8]>>(grid: &[R], palette: &Palette, scale: usize) -> Self {
+        let scale = scale.max(1);
+        let width = grid.iter().map(|row| row.as_ref().len()).max().unwrap_or(0) * scale;
+        let height = grid.len() * scale;
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in grid {
+            let start = pixels.len();
+            for &cell in row.as_ref() {
+                pixels.extend(std::iter::repeat_n(palette.index(cell), scale));
+            }
+            pixels.resize(start + width, 0);
+            for _ in 1..scale {
+                pixels.extend_from_within(start..start + width);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Renders text (such as the [Display](std::fmt::Display) output of a map), one line per row.
+    pub fn from_text(text: &str, palette: &Palette, scale: usize) -> Self {
+        Self::from_grid(
+            &text.lines().map(str::as_bytes).collect::<Vec<_>>(),
+            palette,
+            scale,
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn write_ppm<W: Write>(&self, out: &mut W, palette: &Palette) -> std::io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let colors = palette.colors();
+        for &index in &self.pixels {
+            out.write_all(&colors[index as usize])?;
+        }
+        Ok(())
+    }
+
+    pub fn write_png<W: Write>(&self, out: &mut W, palette: &Palette) -> std::io::Result<()> {
+        png::write(out, self, palette)
+    }
+
+    /// Saves the frame to a file, in PPM or PNG format, based on the extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P, palette: &Palette) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let mut out = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ppm") => self.write_ppm(&mut out, palette)?,
+            Some("png") => self.write_png(&mut out, palette)?,
+            _ => panic!("unsupported image format: {}", path.display()),
+        }
+        out.flush()
+    }
+}
+
+/// Returns where to save the given render output, if rendering was enabled with the `render`
+/// parameter (set to the output directory). Returns None if the directory cannot be created.
+pub fn output_path(file_name: &str) -> Option<PathBuf> {
+    let dir = PathBuf::from(param("render")?);
+    if let Err(err) = create_dir_all(&dir) {
+        eprintln!("cannot create render directory {}: {err}", dir.display());
+        return None;
+    }
+    Some(dir.join(file_name))
+}
+
+/// Reports a failure to save a render output. Rendering is only a side product of solutions, so
+/// it doesn't stop them.
+pub fn report_error(path: &Path, result: std::io::Result<()>) {
+    if let Err(err) = result {
+        eprintln!("failed to save {}: {err}", path.display());
+    }
+}
+
+/// Saves the grid made by `grid` as an image, if rendering was enabled.
+pub fn save_grid<R: AsRef<[u8]>>(
+    file_name: &str,
+    palette: &Palette,
+    grid: impl FnOnce() -> Vec<R>,
+) {
+    if let Some(path) = output_path(file_name) {
+        let frame = Frame::from_grid(&grid(), palette, scale());
+        report_error(&path, frame.save(&path, palette));
+    }
+}
+
+/// Size in pixels of a grid cell, configured with the `render_scale` parameter.
+pub fn scale() -> usize {
+    param("render_scale").map_or(4, |s| s.parse().unwrap())
+}
@@ -0,0 +1,275 @@
+//! Local HTTP service exposing the solutions.
+//!
+//! * `GET /days` - lists the days, as `{"days": [{"day": 1, "name": "..."}, ...]}`
+//! * `POST /run?day=<day>&part=<1|2>[&example=1][&<name>=<value>...]` - runs a solution on the
+//!   request body (or on the example input, if the body is empty in example mode). Other query
+//!   parameters are passed to the solution as context parameters. Responds with
+//!   `{"day": .., "part": .., "answer": "...", "duration_ms": .., "logs": ["...", ...]}`,
+//!   where the answer is the last line of output.
+//!
+//! Requests are handled one at a time, as solutions share global state (mode and parameters).
+
+use crate::api::{capture, set_params};
+use crate::days::DAYS;
+use crate::IS_EXAMPLE;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+/// Time allowed for reading a request or writing a response
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Request {
+    method: String,
+    path: String,
+    query: BTreeMap<String, String>,
+    body: String,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: format!("{{\"error\":{}}}", json_string(message)),
+        }
+    }
+}
+
+/// Listens on the given address (such as `127.0.0.1:8024`) forever.
+pub fn serve(address: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("failed to accept connection: {err}");
+                continue;
+            }
+        };
+        // A client that stops sending or reading must not block the following requests
+        let timeouts = (stream.set_read_timeout(Some(TIMEOUT)))
+            .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)));
+        if let Err(err) = timeouts {
+            eprintln!("failed to set timeouts: {err}");
+            continue;
+        }
+        let response = match read_request(&mut stream) {
+            Ok(request) => handle(request),
+            Err(message) => Response::error(400, &message),
+        };
+        if let Err(err) = write_response(&mut stream, response) {
+            eprintln!("failed to send response: {err}");
+        }
+    }
+    Ok(())
+}
+
+fn handle(request: Request) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/days") => {
+            let days = DAYS
+                .iter()
+                .enumerate()
+                .map(|(index, day)| {
+                    format!(
+                        "{{\"day\":{},\"name\":{}}}",
+                        index + 1,
+                        json_string(day.name)
+                    )
+                })
+                .collect::<Vec<_>>();
+            Response::ok(format!("{{\"days\":[{}]}}", days.join(",")))
+        }
+        ("POST", "/run") => run(request),
+        (_, "/days" | "/run") => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn run(mut request: Request) -> Response {
+    let day: usize = match request.query.remove("day").map(|s| s.parse()) {
+        Some(Ok(day @ 1..=25)) if day <= DAYS.len() => day,
+        _ => return Response::error(400, "missing or invalid day"),
+    };
+    let part: u8 = match request.query.remove("part").as_deref() {
+        Some("1") => 1,
+        Some("2") => 2,
+        _ => return Response::error(400, "missing or invalid part (1 or 2)"),
+    };
+    let example = request
+        .query
+        .remove("example")
+        .is_some_and(|value| value != "0" && value != "false");
+
+    let info = &DAYS[day - 1];
+    let (func, input) = match part {
+        1 => (info.part1, info.example1),
+        _ => (info.part2, info.example2),
+    };
+    let input = if example && request.body.is_empty() {
+        input
+    } else {
+        request.body.as_str()
+    };
+
+    *IS_EXAMPLE.write().unwrap() = example;
+    set_params(request.query);
+    let time = Instant::now();
+    let (result, logs) = capture(|| catch_unwind(AssertUnwindSafe(|| func(input))));
+    let duration = time.elapsed();
+    set_params([]);
+
+    let logs_json = logs
+        .iter()
+        .map(|line| json_string(line))
+        .collect::<Vec<_>>()
+        .join(",");
+    match result {
+        Ok(()) => {
+            let answer = logs
+                .last()
+                .map_or("null".to_owned(), |line| json_string(line));
+            Response::ok(format!(
+                "{{\"day\":{day},\"part\":{part},\"answer\":{answer},\"duration_ms\":{},\"logs\":[{logs_json}]}}",
+                duration.as_secs_f64() * 1000.0
+            ))
+        }
+        Err(payload) => Response {
+            status: 500,
+            body: format!(
+                "{{\"error\":{},\"logs\":[{logs_json}]}}",
+                json_string(&panic_message(payload))
+            ),
+        },
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        format!("solution panicked: {message}")
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        format!("solution panicked: {message}")
+    } else {
+        "solution panicked".to_owned()
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(target)) = (words.next(), words.next()) else {
+        return Err("invalid request line".to_owned());
+    };
+    let method = method.to_owned();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.to_owned();
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((url_decode(name)?, url_decode(value)?))
+        })
+        .collect::<Result<_, String>>()?;
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| "invalid content length".to_owned())?;
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    let body = String::from_utf8(body).map_err(|_| "body is not valid UTF-8".to_owned())?;
+
+    Ok(Request {
+        method,
+        path,
+        query,
+        body,
+    })
+}
+
+fn write_response(stream: &mut TcpStream, response: Response) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+fn url_decode(s: &str) -> Result<String, String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(byte) = iter.next() {
+        bytes.push(match byte {
+            b'+' => b' ',
+            b'%' => {
+                let hex = [iter.next(), iter.next()];
+                let hex = hex.map(|b| b.and_then(|b| (b as char).to_digit(16)));
+                match hex {
+                    [Some(high), Some(low)] => (high * 16 + low) as u8,
+                    _ => return Err(format!("invalid escape in query: {s}")),
+                }
+            }
+            byte => byte,
+        });
+    }
+    String::from_utf8(bytes).map_err(|_| format!("invalid UTF-8 in query: {s}"))
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}